@@ -0,0 +1,649 @@
+use std::collections::HashMap;
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, RenderAssetUsages, io::Reader},
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+
+use crate::sprite_animation::{AnimationSheet, ClipMode, SpriteClip};
+
+const FILE_MAGIC: u16 = 0xA5E0;
+const FRAME_MAGIC: u16 = 0xF1FA;
+
+const OLD_PALETTE_CHUNK: u16 = 0x0004;
+const LAYER_CHUNK: u16 = 0x2004;
+const CEL_CHUNK: u16 = 0x2005;
+const TAGS_CHUNK: u16 = 0x2018;
+const PALETTE_CHUNK: u16 = 0x2019;
+const USER_DATA_CHUNK: u16 = 0x2020;
+
+const USER_DATA_TEXT: u32 = 1;
+/// Colors an indexed file can point at
+const MAX_COLORS: usize = 256;
+
+const LAYER_VISIBLE: u16 = 1;
+const LAYER_REFERENCE: u16 = 64;
+const GROUP_LAYER: u16 = 1;
+const TILEMAP_LAYER: u16 = 2;
+/// The opacity of the layers is set in the file, older files leave it out
+const LAYER_OPACITY_VALID: u32 = 1;
+
+/// Name of the clip with every frame of the file
+pub const ALL_FRAMES: &str = "all";
+/// User data of a tag whose clip follows the life instead of the time
+pub const LIFE_CLIP: &str = "life";
+
+/// A `.aseprite` file with every frame flattened, see
+/// <https://github.com/aseprite/aseprite/blob/main/docs/ase-file-specs.md>.
+/// Visible layers are drawn over each other with their opacity, every blend mode draws as normal
+/// and tilemaps are left out. A tag with [`LIFE_CLIP`] as its user data becomes a life clip.
+#[derive(Debug)]
+pub struct Aseprite {
+    pub size: UVec2,
+    pub frames: Vec<AsepriteFrame>,
+    pub tags: Vec<AsepriteTag>,
+}
+
+#[derive(Debug)]
+pub struct AsepriteFrame {
+    /// Seconds the frame is shown
+    pub duration: f32,
+    /// RGBA, row by row
+    pub pixels: Vec<u8>,
+}
+
+/// A named range of frames, it becomes a clip
+#[derive(Debug)]
+pub struct AsepriteTag {
+    pub name: String,
+    pub from: usize,
+    pub to: usize,
+    pub direction: TagDirection,
+    /// Times the frames play, 0 for ever
+    pub repeat: u16,
+    /// Text of the user data of the tag
+    pub user_data: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagDirection {
+    Forward,
+    Reverse,
+    PingPong,
+    PingPongReverse,
+}
+
+#[derive(Debug)]
+struct Layer {
+    /// Visible and in a visible group
+    visible: bool,
+    opacity: u8,
+    kind: u16,
+}
+
+#[derive(Debug)]
+struct Cel {
+    layer: usize,
+    x: i32,
+    y: i32,
+    z: i32,
+    opacity: u8,
+    image: CelImage,
+}
+
+#[derive(Debug)]
+enum CelImage {
+    /// RGBA pixels
+    Pixels {
+        width: usize,
+        pixels: Vec<u8>,
+    },
+    /// Same as the cel of the layer in that frame
+    Linked(usize),
+    Tilemap,
+}
+
+/// Reads the little endian numbers and strings of the file
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], BevyError> {
+        let bytes = self
+            .bytes
+            .get(self.pos..self.pos + len)
+            .ok_or("the aseprite file ends too early")?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, len: usize) -> Result<(), BevyError> {
+        self.take(len).map(|_| ())
+    }
+
+    fn u8(&mut self) -> Result<u8, BevyError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, BevyError> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn i16(&mut self) -> Result<i16, BevyError> {
+        Ok(self.u16()? as i16)
+    }
+
+    fn u32(&mut self) -> Result<u32, BevyError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn string(&mut self) -> Result<String, BevyError> {
+        let len = self.u16()? as usize;
+        Ok(String::from_utf8_lossy(self.take(len)?).into_owned())
+    }
+
+    fn rest(&mut self) -> &'a [u8] {
+        let rest = &self.bytes[self.pos.min(self.bytes.len())..];
+        self.pos = self.bytes.len();
+        rest
+    }
+}
+
+/// What the chunks of the frames before set up for the next ones
+#[derive(Debug, Default)]
+struct ParseState {
+    layers: Vec<Layer>,
+    /// Visibility of the open groups, by child level
+    groups: Vec<bool>,
+    palette: Vec<[u8; 4]>,
+    tags: Vec<AsepriteTag>,
+    /// Tag the next user data chunk belongs to, they follow the tags chunk one by one
+    user_data_tag: Option<usize>,
+}
+
+/// How the pixels of the file are stored
+#[derive(Debug, Clone, Copy)]
+struct ColorDepth {
+    bits: u16,
+    /// Palette index of the transparent color in indexed files
+    transparent: u8,
+    layer_opacity: bool,
+}
+
+impl Aseprite {
+    pub fn parse(bytes: &[u8]) -> Result<Self, BevyError> {
+        let mut reader = ByteReader::new(bytes);
+        let mut header = ByteReader::new(reader.take(128)?);
+        header.skip(4)?;
+        if header.u16()? != FILE_MAGIC {
+            return Err("not an aseprite file".into());
+        }
+        let frame_count = header.u16()? as usize;
+        let size = UVec2::new(header.u16()? as u32, header.u16()? as u32);
+        if size.x == 0 || size.y == 0 {
+            return Err("the aseprite file has no pixels".into());
+        }
+        let bits = header.u16()?;
+        if ![8, 16, 32].contains(&bits) {
+            return Err(format!("unknown color depth {bits}").into());
+        }
+        let flags = header.u32()?;
+        header.skip(10)?;
+        let depth = ColorDepth {
+            bits,
+            transparent: header.u8()?,
+            layer_opacity: flags & LAYER_OPACITY_VALID != 0,
+        };
+
+        let mut state = ParseState::default();
+        let mut durations = Vec::with_capacity(frame_count);
+        let mut cels = Vec::with_capacity(frame_count);
+        for _ in 0..frame_count {
+            let start = reader.pos;
+            let frame_len = reader.u32()? as usize;
+            if reader.u16()? != FRAME_MAGIC {
+                return Err("broken frame in the aseprite file".into());
+            }
+            let old_chunks = reader.u16()? as usize;
+            durations.push(reader.u16()? as f32 / 1000.);
+            reader.skip(2)?;
+            let chunks = match reader.u32()? as usize {
+                0 => old_chunks,
+                chunks => chunks,
+            };
+
+            let mut frame_cels = Vec::new();
+            for _ in 0..chunks {
+                let chunk_start = reader.pos;
+                let chunk_len = reader.u32()? as usize;
+                let kind = reader.u16()?;
+                let body = chunk_len
+                    .checked_sub(6)
+                    .ok_or("broken chunk in the aseprite file")?;
+                let mut chunk = ByteReader::new(reader.take(body)?);
+                if kind != USER_DATA_CHUNK {
+                    state.user_data_tag = None;
+                }
+                match kind {
+                    OLD_PALETTE_CHUNK if state.palette.is_empty() => {
+                        read_old_palette(&mut chunk, &mut state)?
+                    }
+                    PALETTE_CHUNK => read_palette(&mut chunk, &mut state)?,
+                    LAYER_CHUNK => read_layer(&mut chunk, &mut state)?,
+                    CEL_CHUNK => frame_cels.push(read_cel(&mut chunk, &state, depth)?),
+                    TAGS_CHUNK => read_tags(&mut chunk, &mut state)?,
+                    USER_DATA_CHUNK => read_user_data(&mut chunk, &mut state)?,
+                    _ => {}
+                }
+                reader.pos = chunk_start + chunk_len;
+            }
+            cels.push(frame_cels);
+            reader.pos = start + frame_len;
+        }
+
+        // like the sheet files, a clip cannot point past the last frame
+        for tag in &state.tags {
+            if tag.to >= frame_count {
+                let name = &tag.name;
+                return Err(format!("tag {name} needs frames between 0 and {frame_count}").into());
+            }
+        }
+
+        let frames = durations
+            .into_iter()
+            .enumerate()
+            .map(|(frame, duration)| AsepriteFrame {
+                duration,
+                pixels: flatten(size, &state.layers, &cels, frame, depth),
+            })
+            .collect();
+
+        Ok(Self {
+            size,
+            frames,
+            tags: state.tags,
+        })
+    }
+
+    /// Grid of the frames in the atlas, about as wide as high
+    fn grid(&self) -> UVec2 {
+        let count = self.frames.len().max(1) as u32;
+        let columns = (count as f32).sqrt().ceil() as u32;
+        UVec2::new(columns, count.div_ceil(columns))
+    }
+
+    /// All frames in one image, frame `i` at index `i` of the layout
+    pub fn atlas(&self) -> (Image, TextureAtlasLayout) {
+        let grid = self.grid();
+        let image_size = self.size * grid;
+        let row_len = self.size.x as usize * 4;
+        let mut data = vec![0; image_size.x as usize * image_size.y as usize * 4];
+        for (i, frame) in self.frames.iter().enumerate() {
+            let column = i % grid.x as usize;
+            let row = i / grid.x as usize;
+            for (y, line) in frame.pixels.chunks_exact(row_len).enumerate() {
+                let target_y = row * self.size.y as usize + y;
+                let start = (target_y * image_size.x as usize + column * self.size.x as usize) * 4;
+                data[start..start + row_len].copy_from_slice(line);
+            }
+        }
+
+        let image = Image::new(
+            Extent3d {
+                width: image_size.x,
+                height: image_size.y,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+        let layout = TextureAtlasLayout::from_grid(self.size, grid.x, grid.y, None, None);
+        (image, layout)
+    }
+
+    /// A clip for every tag and [`ALL_FRAMES`], with the durations of the frames
+    pub fn clips(&self) -> HashMap<String, SpriteClip> {
+        let mut clips = HashMap::new();
+        let all = (0..self.frames.len()).collect();
+        clips.insert(ALL_FRAMES.to_string(), self.clip(all, 0));
+
+        for tag in &self.tags {
+            let forward = tag.from..=tag.to;
+            let frames: Vec<usize> = match tag.direction {
+                TagDirection::Forward => forward.collect(),
+                TagDirection::Reverse => forward.rev().collect(),
+                // the ends are not shown twice in a row
+                TagDirection::PingPong => forward
+                    .clone()
+                    .chain((tag.from + 1..tag.to).rev())
+                    .collect(),
+                TagDirection::PingPongReverse => {
+                    forward.clone().rev().chain(tag.from + 1..tag.to).collect()
+                }
+            };
+            let clip = match tag.user_data.trim() {
+                LIFE_CLIP => SpriteClip {
+                    mode: ClipMode::Life,
+                    ..self.clip(frames, 0)
+                },
+                _ => self.clip(frames, tag.repeat),
+            };
+            clips.insert(tag.name.clone(), clip);
+        }
+        clips
+    }
+
+    fn clip(&self, frames: Vec<usize>, repeat: u16) -> SpriteClip {
+        let (frames, mode) = match repeat {
+            0 => (frames, ClipMode::Loop),
+            repeat => (frames.repeat(repeat as usize), ClipMode::Once),
+        };
+        let durations = frames
+            .iter()
+            .map(|&frame| self.frames.get(frame).map_or(0., |frame| frame.duration))
+            .collect();
+        SpriteClip {
+            frames,
+            fps: 0.,
+            durations,
+            mode,
+        }
+    }
+}
+
+fn read_old_palette(chunk: &mut ByteReader, state: &mut ParseState) -> Result<(), BevyError> {
+    let mut index = 0;
+    for _ in 0..chunk.u16()? {
+        index += chunk.u8()? as usize;
+        let count = match chunk.u8()? {
+            0 => 256,
+            count => count as usize,
+        };
+        for _ in 0..count {
+            let [r, g, b] = [chunk.u8()?, chunk.u8()?, chunk.u8()?];
+            set_palette_color(&mut state.palette, index, [r, g, b, 255])?;
+            index += 1;
+        }
+    }
+    Ok(())
+}
+
+fn read_palette(chunk: &mut ByteReader, state: &mut ParseState) -> Result<(), BevyError> {
+    let size = chunk.u32()? as usize;
+    let first = chunk.u32()? as usize;
+    let last = chunk.u32()? as usize;
+    chunk.skip(8)?;
+    if size > MAX_COLORS {
+        return Err(format!("the aseprite file has more than {MAX_COLORS} colors").into());
+    }
+    state.palette.resize(size, [0; 4]);
+    for index in first..=last {
+        let has_name = chunk.u16()? & 1 != 0;
+        let color = [chunk.u8()?, chunk.u8()?, chunk.u8()?, chunk.u8()?];
+        if has_name {
+            chunk.string()?;
+        }
+        set_palette_color(&mut state.palette, index, color)?;
+    }
+    Ok(())
+}
+
+fn set_palette_color(
+    palette: &mut Vec<[u8; 4]>,
+    index: usize,
+    color: [u8; 4],
+) -> Result<(), BevyError> {
+    if index >= MAX_COLORS {
+        return Err(format!("the aseprite file has more than {MAX_COLORS} colors").into());
+    }
+    if palette.len() <= index {
+        palette.resize(index + 1, [0; 4]);
+    }
+    palette[index] = color;
+    Ok(())
+}
+
+fn read_layer(chunk: &mut ByteReader, state: &mut ParseState) -> Result<(), BevyError> {
+    let flags = chunk.u16()?;
+    let kind = chunk.u16()?;
+    let level = chunk.u16()? as usize;
+    chunk.skip(6)?;
+    let opacity = chunk.u8()?;
+
+    state.groups.truncate(level);
+    let parent_visible = state.groups.last().copied().unwrap_or(true);
+    let visible = parent_visible && flags & LAYER_VISIBLE != 0 && flags & LAYER_REFERENCE == 0;
+    if kind == GROUP_LAYER {
+        state.groups.resize(level, parent_visible);
+        state.groups.push(visible);
+    }
+
+    state.layers.push(Layer {
+        visible,
+        opacity,
+        kind,
+    });
+    Ok(())
+}
+
+fn read_cel(
+    chunk: &mut ByteReader,
+    state: &ParseState,
+    depth: ColorDepth,
+) -> Result<Cel, BevyError> {
+    let layer = chunk.u16()? as usize;
+    let x = chunk.i16()? as i32;
+    let y = chunk.i16()? as i32;
+    let opacity = chunk.u8()?;
+    let kind = chunk.u16()?;
+    let z = chunk.i16()? as i32;
+    chunk.skip(5)?;
+
+    let image = match kind {
+        0 | 2 => {
+            let width = chunk.u16()? as usize;
+            let height = chunk.u16()? as usize;
+            if width == 0 || height == 0 {
+                return Err("a cel of the aseprite file has no pixels".into());
+            }
+            let raw = if kind == 0 {
+                chunk.rest().to_vec()
+            } else {
+                miniz_oxide::inflate::decompress_to_vec_zlib(chunk.rest())
+                    .map_err(|error| format!("broken cel in the aseprite file: {error}"))?
+            };
+            let pixels = to_rgba(&raw, depth, &state.palette);
+            if pixels.len() < width * height * 4 {
+                return Err("a cel of the aseprite file misses pixels".into());
+            }
+            CelImage::Pixels { width, pixels }
+        }
+        1 => CelImage::Linked(chunk.u16()? as usize),
+        _ => CelImage::Tilemap,
+    };
+
+    Ok(Cel {
+        layer,
+        x,
+        y,
+        z,
+        opacity,
+        image,
+    })
+}
+
+fn to_rgba(raw: &[u8], depth: ColorDepth, palette: &[[u8; 4]]) -> Vec<u8> {
+    match depth.bits {
+        32 => raw.to_vec(),
+        16 => raw
+            .chunks_exact(2)
+            .flat_map(|pixel| [pixel[0], pixel[0], pixel[0], pixel[1]])
+            .collect(),
+        _ => raw
+            .iter()
+            .flat_map(|&index| {
+                if index == depth.transparent {
+                    [0; 4]
+                } else {
+                    palette.get(index as usize).copied().unwrap_or([0; 4])
+                }
+            })
+            .collect(),
+    }
+}
+
+fn read_tags(chunk: &mut ByteReader, state: &mut ParseState) -> Result<(), BevyError> {
+    let count = chunk.u16()?;
+    chunk.skip(8)?;
+    state.user_data_tag = Some(state.tags.len());
+    for _ in 0..count {
+        let from = chunk.u16()? as usize;
+        let to = chunk.u16()? as usize;
+        let direction = match chunk.u8()? {
+            1 => TagDirection::Reverse,
+            2 => TagDirection::PingPong,
+            3 => TagDirection::PingPongReverse,
+            _ => TagDirection::Forward,
+        };
+        let repeat = chunk.u16()?;
+        chunk.skip(10)?;
+        let name = chunk.string()?;
+        state.tags.push(AsepriteTag {
+            name,
+            from,
+            to: to.max(from),
+            direction,
+            repeat,
+            user_data: String::new(),
+        });
+    }
+    Ok(())
+}
+
+fn read_user_data(chunk: &mut ByteReader, state: &mut ParseState) -> Result<(), BevyError> {
+    let Some(index) = state.user_data_tag else {
+        return Ok(());
+    };
+    state.user_data_tag = Some(index + 1);
+    if chunk.u32()? & USER_DATA_TEXT != 0
+        && let Some(tag) = state.tags.get_mut(index)
+    {
+        tag.user_data = chunk.string()?;
+    }
+    Ok(())
+}
+
+/// The visible cels of the frame drawn over each other, from the bottom layer up
+fn flatten(
+    size: UVec2,
+    layers: &[Layer],
+    cels: &[Vec<Cel>],
+    frame: usize,
+    depth: ColorDepth,
+) -> Vec<u8> {
+    let (width, height) = (size.x as i32, size.y as i32);
+    let mut canvas = vec![0; width as usize * height as usize * 4];
+
+    let mut frame_cels: Vec<&Cel> = cels[frame].iter().collect();
+    // a cel with a z index moves up or down the layers
+    frame_cels.sort_by_key(|cel| (cel.layer as i32 + cel.z, cel.z));
+
+    for cel in frame_cels {
+        let Some(layer) = layers.get(cel.layer) else {
+            continue;
+        };
+        if !layer.visible || layer.kind == GROUP_LAYER || layer.kind == TILEMAP_LAYER {
+            continue;
+        }
+        let source = match cel.image {
+            CelImage::Linked(linked) => cels
+                .get(linked)
+                .and_then(|linked| linked.iter().find(|other| other.layer == cel.layer))
+                .unwrap_or(cel),
+            _ => cel,
+        };
+        let CelImage::Pixels {
+            width: cel_width,
+            pixels,
+        } = &source.image
+        else {
+            continue;
+        };
+        let layer_opacity = if depth.layer_opacity {
+            layer.opacity
+        } else {
+            255
+        };
+        let opacity = source.opacity as f32 / 255. * layer_opacity as f32 / 255.;
+
+        for (i, pixel) in pixels.chunks_exact(4).enumerate() {
+            let x = source.x + (i % cel_width) as i32;
+            let y = source.y + (i / cel_width) as i32;
+            if x < 0 || y < 0 || x >= width || y >= height {
+                continue;
+            }
+            let at = (y * width + x) as usize * 4;
+            blend(&mut canvas[at..at + 4], pixel, opacity);
+        }
+    }
+    canvas
+}
+
+/// Draws the pixel over the canvas, both with straight alpha
+fn blend(canvas: &mut [u8], pixel: &[u8], opacity: f32) {
+    let alpha = pixel[3] as f32 / 255. * opacity;
+    if alpha <= 0. {
+        return;
+    }
+    let below = canvas[3] as f32 / 255.;
+    let out = alpha + below * (1. - alpha);
+    for c in 0..3 {
+        let color = pixel[c] as f32 * alpha + canvas[c] as f32 * below * (1. - alpha);
+        canvas[c] = (color / out).round() as u8;
+    }
+    canvas[3] = (out * 255.).round() as u8;
+}
+
+/// Loads `.aseprite` and `.ase` files as an [`AnimationSheet`] with the flattened frames
+/// as its image and a clip for every tag
+#[derive(Debug, Default, TypePath)]
+pub struct AsepriteLoader;
+
+impl AssetLoader for AsepriteLoader {
+    type Asset = AnimationSheet;
+    type Settings = ();
+    type Error = BevyError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<AnimationSheet, BevyError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let aseprite = Aseprite::parse(&bytes)?;
+        let (image, layout) = aseprite.atlas();
+
+        Ok(AnimationSheet {
+            image: Some(load_context.add_labeled_asset("image".to_string(), image)),
+            layout: load_context.add_labeled_asset("layout".to_string(), layout),
+            clips: aseprite.clips(),
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["aseprite", "ase"]
+    }
+}
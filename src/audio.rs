@@ -0,0 +1,491 @@
+use std::{collections::HashMap, f32::consts::TAU};
+
+use bevy::{
+    audio::{AudioSinkPlayback, SpatialScale, Volume},
+    prelude::*,
+};
+use rand::RngExt;
+
+/// Sample rate of the synthesized sounds
+const SYNTH_RATE: u32 = 22050;
+/// World units per unit of the spatial audio, a sound this far to the side is only heard on that side
+const SPATIAL_UNIT: f32 = 200.;
+/// Distance of the ears of the [`SpatialListener`] on the camera
+pub const EAR_GAP: f32 = SPATIAL_UNIT;
+
+/// Plays every sound of the game through the [`AudioManager`], with a volume per [`AudioBus`]
+pub struct GameAudioPlugin;
+
+impl Plugin for GameAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AudioBuses>()
+            .add_systems(Startup, setup_audio)
+            .add_systems(
+                Update,
+                (
+                    play_queued_sounds,
+                    update_loops,
+                    apply_bus_volumes.run_if(resource_changed::<AudioBuses>),
+                )
+                    .chain()
+                    .run_if(resource_exists::<AudioManager>),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AudioBus {
+    Sfx,
+    Ui,
+    Music,
+}
+
+/// Volume of every bus, multiplied by the master volume
+#[derive(Resource, Debug, Clone)]
+pub struct AudioBuses {
+    pub master: f32,
+    pub sfx: f32,
+    pub ui: f32,
+    pub music: f32,
+}
+
+impl Default for AudioBuses {
+    fn default() -> Self {
+        Self {
+            master: 1.,
+            sfx: 1.,
+            ui: 1.,
+            music: 0.6,
+        }
+    }
+}
+
+impl AudioBuses {
+    pub fn volume(&self, bus: AudioBus) -> f32 {
+        self.master
+            * match bus {
+                AudioBus::Sfx => self.sfx,
+                AudioBus::Ui => self.ui,
+                AudioBus::Music => self.music,
+            }
+    }
+}
+
+/// Every sound the game plays
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Sound {
+    Click,
+    /// Loops while the laser burns the player cube
+    CubeHit,
+    /// Loops while the laser burns a nut
+    NutHit,
+    /// A nut breaks free of its ice
+    NutRelease,
+    /// Loops for the whole round, follows what the beam does
+    LaserHum,
+    /// The beam found one more reflection
+    ReflectStinger,
+    /// Short rising chime on top of the release, the nut is won
+    KillStinger,
+}
+
+/// Where the samples of a [`Sound`] come from
+enum SoundSource {
+    File(&'static str),
+    /// Mono samples at [`SYNTH_RATE`]
+    Synth(fn() -> Vec<f32>),
+}
+
+impl Sound {
+    const ALL: [Sound; 7] = [
+        Sound::Click,
+        Sound::CubeHit,
+        Sound::NutHit,
+        Sound::NutRelease,
+        Sound::LaserHum,
+        Sound::ReflectStinger,
+        Sound::KillStinger,
+    ];
+
+    fn source(&self) -> SoundSource {
+        match self {
+            Sound::Click => SoundSource::File("embedded://button.wav"),
+            Sound::CubeHit => SoundSource::File("embedded://cube_hit.wav"),
+            // the release is the hit sound played faster
+            Sound::NutHit | Sound::NutRelease => SoundSource::File("embedded://nut_hit.wav"),
+            Sound::LaserHum => SoundSource::Synth(synth_hum),
+            Sound::ReflectStinger => SoundSource::Synth(synth_ping),
+            Sound::KillStinger => SoundSource::Synth(synth_chime),
+        }
+    }
+
+    pub fn bus(&self) -> AudioBus {
+        match self {
+            Sound::Click => AudioBus::Ui,
+            _ => AudioBus::Sfx,
+        }
+    }
+
+    fn volume(&self) -> f32 {
+        match self {
+            Sound::NutRelease => 0.7,
+            Sound::LaserHum => 0.4,
+            Sound::ReflectStinger => 0.5,
+            Sound::KillStinger => 0.6,
+            _ => 1.,
+        }
+    }
+
+    fn speed(&self) -> f32 {
+        match self {
+            Sound::NutRelease => 1.6,
+            _ => 1.,
+        }
+    }
+
+    /// Random change of the speed every time it starts, so repeats do not sound the same
+    fn pitch_variation(&self) -> f32 {
+        match self {
+            Sound::NutHit => 0.06,
+            Sound::NutRelease => 0.08,
+            Sound::ReflectStinger | Sound::KillStinger => 0.04,
+            _ => 0.,
+        }
+    }
+
+    /// Plays where it happens in the forest, panned and quieter further away
+    fn spatial(&self) -> bool {
+        matches!(
+            self,
+            Sound::CubeHit | Sound::NutHit | Sound::NutRelease | Sound::KillStinger
+        )
+    }
+
+    fn looping(&self) -> bool {
+        matches!(self, Sound::CubeHit | Sound::NutHit | Sound::LaserHum)
+    }
+
+    /// How many of it can play at once, the oldest one is cut off by another one
+    fn voices(&self) -> usize {
+        match self {
+            Sound::NutRelease | Sound::ReflectStinger | Sound::KillStinger => 4,
+            _ => 1,
+        }
+    }
+
+    /// Speed with a random pitch variation
+    fn varied_speed(&self) -> f32 {
+        let variation = self.pitch_variation();
+        if variation == 0. {
+            return self.speed();
+        }
+        self.speed() * (1. + rand::rng().random_range(-variation..variation))
+    }
+}
+
+/// Speed and volume a loop is played with on top of its own
+#[derive(Debug, Clone, Copy)]
+struct LoopModulation {
+    speed: f32,
+    volume: f32,
+}
+
+impl Default for LoopModulation {
+    fn default() -> Self {
+        Self {
+            speed: 1.,
+            volume: 1.,
+        }
+    }
+}
+
+/// Plays one shot sounds on a few reused voices and keeps loops running
+/// while anybody still wants them
+#[derive(Resource, Debug)]
+pub struct AudioManager {
+    handles: HashMap<Sound, Handle<AudioSource>>,
+    voices: HashMap<Sound, Vec<Entity>>,
+    next_voice: HashMap<Sound, usize>,
+    /// Users of every loop, it plays while there is at least one
+    loop_users: HashMap<Sound, u32>,
+    /// Speed of every loop since its last start, with the pitch variation
+    loop_speeds: HashMap<Sound, f32>,
+    modulations: HashMap<Sound, LoopModulation>,
+    /// World position of the spatial loops
+    loop_positions: HashMap<Sound, Vec2>,
+    queued: Vec<QueuedSound>,
+}
+
+#[derive(Debug)]
+struct QueuedSound {
+    sound: Sound,
+    speed: f32,
+    pos: Vec2,
+}
+
+impl AudioManager {
+    /// Plays the sound once in the next frame
+    pub fn play(&mut self, sound: Sound) {
+        self.play_at(sound, Vec2::ZERO);
+    }
+
+    /// Plays the sound once in the next frame, from `pos` in the world if it is spatial
+    pub fn play_at(&mut self, sound: Sound, pos: Vec2) {
+        self.queued.push(QueuedSound {
+            sound,
+            speed: sound.varied_speed(),
+            pos,
+        });
+    }
+
+    pub fn start_loop(&mut self, sound: Sound) {
+        let users = self.loop_users.entry(sound).or_default();
+        if *users == 0 {
+            self.loop_speeds.insert(sound, sound.varied_speed());
+        }
+        *users += 1;
+    }
+
+    pub fn is_looping(&self, sound: Sound) -> bool {
+        self.loop_users.get(&sound).is_some_and(|users| *users > 0)
+    }
+
+    /// Ends one [`AudioManager::start_loop`], the loop pauses after the last one
+    pub fn stop_loop(&mut self, sound: Sound) {
+        let users = self.loop_users.entry(sound).or_default();
+        *users = users.saturating_sub(1);
+    }
+
+    /// Scales the speed and the volume of a loop, until the next call
+    pub fn modulate_loop(&mut self, sound: Sound, speed: f32, volume: f32) {
+        self.modulations
+            .insert(sound, LoopModulation { speed, volume });
+    }
+
+    /// Moves a spatial loop to `pos` in the world
+    pub fn place_loop(&mut self, sound: Sound, pos: Vec2) {
+        self.loop_positions.insert(sound, pos);
+    }
+
+    fn loop_speed(&self, sound: Sound) -> f32 {
+        let speed = self
+            .loop_speeds
+            .get(&sound)
+            .copied()
+            .unwrap_or(sound.speed());
+        speed * self.modulation(sound).speed
+    }
+
+    fn modulation(&self, sound: Sound) -> LoopModulation {
+        self.modulations.get(&sound).copied().unwrap_or_default()
+    }
+}
+
+/// A voice of the [`AudioManager`] that plays `sound`
+#[derive(Debug, Component)]
+struct SoundVoice(Sound);
+
+fn playback(sound: Sound, speed: f32, buses: &AudioBuses) -> PlaybackSettings {
+    let settings = if sound.looping() {
+        PlaybackSettings::LOOP.paused()
+    } else {
+        PlaybackSettings::ONCE
+    };
+    settings
+        .with_volume(Volume::Linear(sound.volume() * buses.volume(sound.bus())))
+        .with_speed(speed)
+        .with_spatial(sound.spatial())
+        .with_spatial_scale(SpatialScale::new_2d(1. / SPATIAL_UNIT))
+}
+
+/// A low buzz of one second, whole periods only so it loops without a click
+fn synth_hum() -> Vec<f32> {
+    (0..SYNTH_RATE)
+        .map(|i| {
+            let t = i as f32 / SYNTH_RATE as f32;
+            let partial = |harmonic: f32| (TAU * 110. * harmonic * t).sin() / harmonic;
+            (partial(1.) + partial(2.) + partial(3.) + partial(5.)) * 0.3
+        })
+        .collect()
+}
+
+/// A short bright ping
+fn synth_ping() -> Vec<f32> {
+    (0..SYNTH_RATE / 4)
+        .map(|i| {
+            let t = i as f32 / SYNTH_RATE as f32;
+            let decay = (-t * 18.).exp();
+            ((TAU * 1320. * t).sin() + (TAU * 1980. * t).sin() * 0.5) * decay * 0.5
+        })
+        .collect()
+}
+
+/// Two quick notes going up, the second one rings out
+fn synth_chime() -> Vec<f32> {
+    let gap = SYNTH_RATE / 16;
+    (0..SYNTH_RATE * 3 / 8)
+        .map(|i| {
+            let (freq, start) = if i < gap { (880., 0) } else { (1320., gap) };
+            let t = (i - start) as f32 / SYNTH_RATE as f32;
+            let decay = (-t * 12.).exp();
+            ((TAU * freq * t).sin() + (TAU * freq * 2. * t).sin() * 0.3) * decay * 0.5
+        })
+        .collect()
+}
+
+/// Encodes mono samples as a 16 bit wav, the format the audio player decodes
+fn wav(samples: &[f32]) -> AudioSource {
+    let data_size = samples.len() as u32 * 2;
+    let mut bytes = Vec::with_capacity(44 + data_size as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_size).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    // pcm, mono
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&SYNTH_RATE.to_le_bytes());
+    bytes.extend_from_slice(&(SYNTH_RATE * 2).to_le_bytes());
+    bytes.extend_from_slice(&2u16.to_le_bytes());
+    bytes.extend_from_slice(&16u16.to_le_bytes());
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_size.to_le_bytes());
+    for sample in samples {
+        let sample = (sample.clamp(-1., 1.) * i16::MAX as f32) as i16;
+        bytes.extend_from_slice(&sample.to_le_bytes());
+    }
+
+    AudioSource {
+        bytes: bytes.into(),
+    }
+}
+
+fn setup_audio(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut sources: ResMut<Assets<AudioSource>>,
+    buses: Res<AudioBuses>,
+) {
+    let mut manager = AudioManager {
+        handles: HashMap::new(),
+        voices: HashMap::new(),
+        next_voice: HashMap::new(),
+        loop_users: HashMap::new(),
+        loop_speeds: HashMap::new(),
+        modulations: HashMap::new(),
+        loop_positions: HashMap::new(),
+        queued: Vec::new(),
+    };
+
+    for sound in Sound::ALL {
+        let handle = match sound.source() {
+            SoundSource::File(path) => asset_server.load(path),
+            SoundSource::Synth(synth) => sources.add(wav(&synth())),
+        };
+        let voices = (0..sound.voices())
+            .map(|_| {
+                let mut voice = commands.spawn((SoundVoice(sound), Transform::default()));
+                // loops play paused from the start, one shots get their player when played
+                if sound.looping() {
+                    let settings = playback(sound, sound.speed(), &buses);
+                    voice.insert((AudioPlayer(handle.clone()), settings));
+                }
+                voice.id()
+            })
+            .collect();
+
+        manager.voices.insert(sound, voices);
+        manager.handles.insert(sound, handle);
+    }
+
+    commands.insert_resource(manager);
+}
+
+fn play_queued_sounds(
+    mut manager: ResMut<AudioManager>,
+    buses: Res<AudioBuses>,
+    mut commands: Commands,
+) {
+    let manager = &mut *manager;
+    for queued in manager.queued.drain(..) {
+        let sound = queued.sound;
+        let voices = &manager.voices[&sound];
+        let next = manager.next_voice.entry(sound).or_default();
+        let voice = voices[*next % voices.len()];
+        *next += 1;
+
+        // a new player on the voice starts the sound over, dropping the old sink stops it
+        commands
+            .entity(voice)
+            .remove::<(AudioSink, SpatialAudioSink)>()
+            .insert((
+                AudioPlayer(manager.handles[&sound].clone()),
+                playback(sound, queued.speed, &buses),
+                Transform::from_translation(queued.pos.extend(0.)),
+            ));
+    }
+}
+
+fn update_loops(
+    manager: Res<AudioManager>,
+    buses: Res<AudioBuses>,
+    mut sinks: Query<(&mut AudioSink, &SoundVoice)>,
+    mut spatial_sinks: Query<(&mut SpatialAudioSink, &SoundVoice, &mut Transform)>,
+) {
+    for (mut sink, voice) in &mut sinks {
+        update_loop(&mut *sink, voice.0, &manager, &buses);
+    }
+    for (mut sink, voice, mut transform) in &mut spatial_sinks {
+        update_loop(&mut *sink, voice.0, &manager, &buses);
+        if let Some(pos) = manager.loop_positions.get(&voice.0) {
+            // only on a move, a changed transform updates the emitter
+            let translation = pos.extend(0.);
+            if transform.translation != translation {
+                transform.translation = translation;
+            }
+        }
+    }
+}
+
+fn update_loop(
+    sink: &mut impl AudioSinkPlayback,
+    sound: Sound,
+    manager: &AudioManager,
+    buses: &AudioBuses,
+) {
+    if !sound.looping() {
+        return;
+    }
+
+    let looping = manager.is_looping(sound);
+    if looping && sink.is_paused() {
+        sink.play();
+    } else if !looping && !sink.is_paused() {
+        sink.pause();
+    }
+
+    let modulation = manager.modulation(sound);
+    sink.set_speed(manager.loop_speed(sound));
+    sink.set_volume(Volume::Linear(
+        sound.volume() * modulation.volume * buses.volume(sound.bus()),
+    ));
+}
+
+fn apply_bus_volumes(
+    buses: Res<AudioBuses>,
+    mut sinks: Query<(&mut AudioSink, &SoundVoice)>,
+    mut spatial_sinks: Query<(&mut SpatialAudioSink, &SoundVoice)>,
+) {
+    let volume = |sound: Sound| Volume::Linear(sound.volume() * buses.volume(sound.bus()));
+    // loops get their volume every frame
+    for (mut sink, voice) in &mut sinks {
+        if !voice.0.looping() {
+            sink.set_volume(volume(voice.0));
+        }
+    }
+    for (mut sink, voice) in &mut spatial_sinks {
+        if !voice.0.looping() {
+            sink.set_volume(volume(voice.0));
+        }
+    }
+}
@@ -0,0 +1,249 @@
+use bevy::math::Vec2;
+use cozy_winter::{
+    Money, PlayerStats, UpgradeType, cli_arg, cli_flag,
+    define_upgrades::get_upgrades,
+    forest::{HALF_SIZE_CUBE, HALF_SIZE_SPAWN_FRAME, LASER_SOURCE},
+    report::{Row, print_table},
+};
+
+/// Seconds the cube sits in the laser to turn onto the next nut
+const AIM_TIME: f32 = 0.5;
+
+/// Simulates buying upgrades between rounds with a simple income model:
+/// `cargo run --bin balance -- [--strategy cheapest|value|round-robin|all] [--rounds 30] [--csv]`
+///
+/// Prints money, stats and upgrade levels after every round, to see where the
+/// progress stalls or runs away.
+fn main() {
+    let rounds = cli_arg("--rounds")
+        .and_then(|r| r.parse().ok())
+        .unwrap_or(30);
+    let csv = cli_flag("--csv");
+    let strategies = match cli_arg("--strategy").as_deref() {
+        None | Some("all") => Strategy::ALL.to_vec(),
+        Some(name) => match Strategy::ALL.into_iter().find(|s| s.name() == name) {
+            Some(strategy) => vec![strategy],
+            None => {
+                println!("Unknown strategy '{}'", name);
+                return;
+            }
+        },
+    };
+
+    let header = header(&get_upgrades());
+    if csv {
+        println!("{}", header.join(","));
+    }
+
+    for strategy in strategies {
+        let rows = simulate(strategy, rounds);
+        if csv {
+            for row in rows {
+                println!("{}", row.join(","));
+            }
+        } else {
+            print_table(&header, &rows);
+            println!();
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Strategy {
+    /// Always the cheapest upgrade
+    Cheapest,
+    /// The upgrade that adds the most income for its cost
+    ValuePerCost,
+    /// One of every upgrade in turn
+    RoundRobin,
+}
+
+impl Strategy {
+    const ALL: [Strategy; 3] = [
+        Strategy::Cheapest,
+        Strategy::ValuePerCost,
+        Strategy::RoundRobin,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Strategy::Cheapest => "cheapest",
+            Strategy::ValuePerCost => "value",
+            Strategy::RoundRobin => "round-robin",
+        }
+    }
+}
+
+/// Result of one round in the income model
+#[derive(Debug, Default)]
+struct RoundOutcome {
+    income: i32,
+    nuts: i32,
+    time: f32,
+}
+
+/// Plays one round on paper: the cube drains while it is in the laser,
+/// every nut costs its life plus the aim time, and nuts out of laser reach are skipped.
+fn play_round(stats: &PlayerStats) -> RoundOutcome {
+    let reach = laser_reach(stats.laser_length);
+    let respawn_time = stats.nuts_respawn_time.max(0.1);
+    let kill_time = stats.nut_base_life.max(0.) / stats.dmg + AIM_TIME;
+    // seconds the cube survives in the laser
+    let mut beam_time = stats.cube_max_life / stats.dmg;
+
+    let mut outcome = RoundOutcome::default();
+    let mut nuts = stats.start_nuts as f32 * reach;
+    let mut next_spawn = respawn_time;
+
+    loop {
+        if nuts >= 1. {
+            if beam_time < kill_time {
+                break;
+            }
+            beam_time -= kill_time;
+            outcome.time += kill_time;
+            outcome.nuts += 1;
+            nuts -= 1.;
+        } else if stats.respawn_nuts as f32 * reach > 0. {
+            // wait out of the laser for the next nuts
+            outcome.time = outcome.time.max(next_spawn);
+        } else {
+            break;
+        }
+
+        while next_spawn <= outcome.time {
+            nuts += stats.respawn_nuts as f32 * reach;
+            next_spawn += respawn_time;
+        }
+    }
+
+    // the respawned nuts come in every kind, so a nut is worth its average
+    outcome.income = (outcome.nuts as f32 * stats.average_value()).round() as i32;
+    outcome
+}
+
+/// Share of the spawn frame the laser reaches from its source
+fn laser_reach(laser_length: f32) -> f32 {
+    let steps = 20;
+    let mut inside = 0;
+    for x in 0..=steps {
+        for y in 0..=steps {
+            let t = Vec2::new(x as f32, y as f32) / steps as f32;
+            let pos = (t * 2. - 1.) * HALF_SIZE_SPAWN_FRAME;
+            if pos.distance(LASER_SOURCE) + HALF_SIZE_CUBE <= laser_length {
+                inside += 1;
+            }
+        }
+    }
+    inside as f32 / ((steps + 1) * (steps + 1)) as f32
+}
+
+/// Income a round gains from buying one level of the upgrade
+fn income_gain(upgrade: &UpgradeType, stats: &PlayerStats) -> i32 {
+    let mut upgrade = upgrade.clone();
+    let mut stats_after = stats.clone();
+    let mut money = Money(upgrade.cost);
+    (upgrade.increase_value)(&mut upgrade, &mut stats_after, &mut money);
+    play_round(&stats_after).income - play_round(stats).income
+}
+
+/// Index of the upgrade the strategy buys next, `None` ends the shopping
+fn pick(
+    strategy: Strategy,
+    upgrades: &[UpgradeType],
+    stats: &PlayerStats,
+    money: &Money,
+    next: &mut usize,
+) -> Option<usize> {
+    let affordable = |u: &UpgradeType| u.cur_up_count < u.max_up_count && u.cost <= money.0;
+    let candidates = upgrades.iter().enumerate().filter(|(_, u)| affordable(u));
+
+    match strategy {
+        Strategy::Cheapest => candidates.min_by_key(|(_, u)| u.cost).map(|(i, _)| i),
+        Strategy::ValuePerCost => candidates
+            .map(|(i, u)| (i, income_gain(u, stats) as f32 / u.cost.max(1) as f32))
+            .filter(|(_, value)| *value > 0.)
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i),
+        Strategy::RoundRobin => {
+            let index = (0..upgrades.len())
+                .map(|i| (*next + i) % upgrades.len())
+                .find(|i| affordable(&upgrades[*i]))?;
+            *next = index + 1;
+            Some(index)
+        }
+    }
+}
+
+fn header(upgrades: &[UpgradeType]) -> Row {
+    let mut header: Row = [
+        "strategy",
+        "round",
+        "time",
+        "nuts",
+        "income",
+        "spent",
+        "money",
+        "dmg",
+        "laser",
+        "life",
+        "nut_life",
+        "nut_value",
+        "respawn",
+        "respawn_time",
+        "start_nuts",
+    ]
+    .map(String::from)
+    .to_vec();
+    header.extend(upgrades.iter().map(|u| {
+        format!("{} {}", u.title, u.value_hint)
+            .to_lowercase()
+            .replace(' ', "_")
+    }));
+    header
+}
+
+fn simulate(strategy: Strategy, rounds: usize) -> Vec<Row> {
+    let mut stats = PlayerStats::default();
+    let mut money = Money(0);
+    let mut upgrades = get_upgrades();
+    let mut next = 0;
+    let mut rows = vec![];
+
+    for round in 1..=rounds {
+        let outcome = play_round(&stats);
+        money.0 += outcome.income;
+
+        let before = money.0;
+        while let Some(index) = pick(strategy, &upgrades, &stats, &money, &mut next) {
+            let upgrade = &mut upgrades[index];
+            let count = upgrade.cur_up_count;
+            (upgrade.increase_value)(upgrade, &mut stats, &mut money);
+            if upgrade.cur_up_count == count {
+                break;
+            }
+        }
+
+        let mut row: Row = vec![
+            strategy.name().into(),
+            round.to_string(),
+            format!("{:.1}", outcome.time),
+            outcome.nuts.to_string(),
+            outcome.income.to_string(),
+            (before - money.0).to_string(),
+            money.0.to_string(),
+            stats.dmg.to_string(),
+            stats.laser_length.to_string(),
+            stats.cube_max_life.to_string(),
+            stats.nut_base_life.to_string(),
+            stats.base_nut_value.to_string(),
+            stats.respawn_nuts.to_string(),
+            format!("{:.1}", stats.nuts_respawn_time),
+            stats.start_nuts.to_string(),
+        ];
+        row.extend(upgrades.iter().map(|u| u.cur_up_count.to_string()));
+        rows.push(row);
+    }
+
+    rows
+}
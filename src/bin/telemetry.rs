@@ -0,0 +1,203 @@
+use std::{fs, path::Path, process::ExitCode};
+
+use cozy_winter::{
+    cli_flag,
+    report::{Row, print_table},
+    telemetry::{TELEMETRY_DIR, TelemetryEvent, TelemetryRecord},
+};
+
+/// Sums up telemetry logs into one row per round:
+/// `cargo run --bin telemetry -- [logs or folders of logs] [--csv]`
+///
+/// Reads the `telemetry` folder if nothing is given. Upgrades count for the round
+/// they were bought after. Logs that cannot be read are reported on stderr and
+/// fail the run, the table still sums up the others.
+fn main() -> ExitCode {
+    let csv = cli_flag("--csv");
+    let mut paths: Vec<String> = std::env::args()
+        .skip(1)
+        .filter(|arg| !arg.starts_with("--"))
+        .collect();
+    if paths.is_empty() {
+        paths.push(TELEMETRY_DIR.into());
+    }
+
+    let mut failed = false;
+    let mut files = Vec::new();
+    for path in &paths {
+        match log_files(Path::new(path)) {
+            Ok(found) => files.extend(found),
+            Err(err) => {
+                eprintln!("Cannot read '{}': {}", path, err);
+                failed = true;
+            }
+        }
+    }
+    files.sort();
+
+    let mut rounds = Vec::new();
+    for file in &files {
+        match read_log(file) {
+            Ok(records) => rounds.extend(summarize(file, &records)),
+            Err(err) => {
+                eprintln!("Cannot read '{}': {}", file.display(), err);
+                failed = true;
+            }
+        }
+    }
+
+    let header = header();
+    let rows: Vec<Row> = rounds.iter().map(RoundSummary::row).collect();
+    if csv {
+        println!("{}", header.join(","));
+        for row in rows {
+            println!("{}", row.join(","));
+        }
+    } else {
+        print_table(&header, &rows);
+        let finished: Vec<&RoundSummary> = rounds.iter().filter(|r| r.score.is_some()).collect();
+        if !finished.is_empty() {
+            let total: i32 = finished.iter().filter_map(|r| r.score).sum();
+            println!(
+                "\n{} sessions, {} finished rounds, {:.1} nuts per round",
+                files.len(),
+                finished.len(),
+                total as f32 / finished.len() as f32
+            );
+        }
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+#[derive(Debug, Default)]
+struct RoundSummary {
+    session: String,
+    round: u32,
+    mode: String,
+    start: f32,
+    end: Option<f32>,
+    /// Money earned, none if the log ends in the round
+    score: Option<i32>,
+    nuts: u32,
+    /// Value of the most valuable nut
+    best_nut: i32,
+    cubes_lost: u32,
+    /// What took the last cube
+    death: String,
+    upgrades: u32,
+    spent: i32,
+}
+
+impl RoundSummary {
+    fn row(&self) -> Row {
+        let length = self.end.map(|end| end - self.start);
+        vec![
+            self.session.clone(),
+            self.round.to_string(),
+            self.mode.clone(),
+            length.map(|l| format!("{:.1}", l)).unwrap_or("-".into()),
+            self.score.map(|s| s.to_string()).unwrap_or("-".into()),
+            self.nuts.to_string(),
+            self.best_nut.to_string(),
+            self.cubes_lost.to_string(),
+            self.death.clone(),
+            self.upgrades.to_string(),
+            self.spent.to_string(),
+        ]
+    }
+}
+
+fn header() -> Row {
+    [
+        "session",
+        "round",
+        "mode",
+        "seconds",
+        "score",
+        "nuts",
+        "best nut",
+        "cubes lost",
+        "death",
+        "upgrades",
+        "spent",
+    ]
+    .map(String::from)
+    .to_vec()
+}
+
+/// The file itself or every `.jsonl` file in the folder
+fn log_files(path: &Path) -> Result<Vec<std::path::PathBuf>, String> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let entries = fs::read_dir(path).map_err(|e| e.to_string())?;
+    Ok(entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "jsonl"))
+        .collect())
+}
+
+fn read_log(path: &Path) -> Result<Vec<TelemetryRecord>, String> {
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(i, line)| serde_json::from_str(line).map_err(|e| format!("line {}: {}", i + 1, e)))
+        .collect()
+}
+
+fn summarize(file: &Path, records: &[TelemetryRecord]) -> Vec<RoundSummary> {
+    // the session is named by its start time
+    let session = file
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut rounds: Vec<RoundSummary> = Vec::new();
+
+    for record in records {
+        if let TelemetryEvent::RoundStart { mode, .. } = &record.event {
+            rounds.push(RoundSummary {
+                session: session.clone(),
+                round: record.round,
+                mode: mode.title().into(),
+                start: record.time,
+                death: "-".into(),
+                ..Default::default()
+            });
+            continue;
+        }
+
+        let Some(round) = rounds.last_mut() else {
+            continue;
+        };
+        match &record.event {
+            TelemetryEvent::RoundEnd { score, .. } => {
+                round.end = Some(record.time);
+                round.score = Some(*score);
+            }
+            TelemetryEvent::NutKill { value, .. } => {
+                round.nuts += 1;
+                round.best_nut = round.best_nut.max(*value);
+            }
+            TelemetryEvent::CubeDeath { cause, cubes_left } => {
+                round.cubes_lost += 1;
+                if *cubes_left == 0 {
+                    round.death = format!("{:?}", cause);
+                }
+            }
+            TelemetryEvent::Purchase { cost, .. } => {
+                round.upgrades += 1;
+                round.spent += cost;
+            }
+            TelemetryEvent::SessionStart { .. } | TelemetryEvent::RoundStart { .. } => {}
+        }
+    }
+    rounds
+}
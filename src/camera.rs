@@ -0,0 +1,137 @@
+use bevy::prelude::*;
+
+use crate::{
+    NutType, PlayerStats, cli_arg, cli_flag,
+    events::{CubeDamaged, CubeLost, NutDestroyed},
+};
+
+/// Trauma the camera loses per second
+const TRAUMA_DECAY: f32 = 1.5;
+/// Pixels the camera moves at full trauma
+const MAX_OFFSET: f32 = 12.;
+/// Radians the camera turns at full trauma
+const MAX_ANGLE: f32 = 0.04;
+/// How fast the shake wobbles
+const SHAKE_SPEED: f32 = 30.;
+/// Share the view shrinks by at the start of a zoom punch
+const PUNCH_ZOOM: f32 = 0.08;
+/// Seconds a zoom punch takes to settle
+const PUNCH_TIME: f32 = 0.3;
+
+/// Shakes the camera on big hits and punches in on a Diamant, see [`CameraSettings`]
+pub struct CameraEffectsPlugin;
+
+impl Plugin for CameraEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(CameraSettings::from_args())
+            .add_systems(
+                Update,
+                (add_trauma, move_camera)
+                    .chain()
+                    .run_if(resource_exists::<PlayerStats>),
+            );
+    }
+}
+
+/// How the camera moves, from `--shake <0 to 1>` and `--reduced-motion`
+#[derive(Debug, Resource, Clone)]
+pub struct CameraSettings {
+    /// Strength of the shake, 0 to 1
+    pub shake: f32,
+    /// No shake and no zoom at all
+    pub reduced_motion: bool,
+}
+
+impl CameraSettings {
+    fn from_args() -> Self {
+        let shake = cli_arg("--shake").and_then(|shake| shake.parse::<f32>().ok());
+        Self {
+            shake: shake.unwrap_or(1.).clamp(0., 1.),
+            reduced_motion: cli_flag("--reduced-motion"),
+        }
+    }
+}
+
+/// On the camera, the shake is `trauma` squared so small hits barely move it
+#[derive(Debug, Component)]
+pub struct CameraShake {
+    /// 0 to 1
+    pub trauma: f32,
+    /// 0 to 1, left of the zoom punch
+    pub punch: f32,
+    /// Transform of the camera without any shake, the cursor is read with it
+    pub rest: Transform,
+    time: f32,
+}
+
+impl CameraShake {
+    pub fn new(rest: Transform) -> Self {
+        Self {
+            trauma: 0.,
+            punch: 0.,
+            rest,
+            time: 0.,
+        }
+    }
+
+    pub fn add_trauma(&mut self, trauma: f32) {
+        self.trauma = (self.trauma + trauma).min(1.);
+    }
+}
+
+/// Trauma of every big moment: a quarter of the cube melted, a rare nut broke or a cube was lost
+fn add_trauma(
+    mut shake: Single<&mut CameraShake>,
+    mut damaged: MessageReader<CubeDamaged>,
+    mut destroyed: MessageReader<NutDestroyed>,
+    mut lost: MessageReader<CubeLost>,
+    stats: Res<PlayerStats>,
+) {
+    let quarter = stats.cube_max_life / 4.;
+    for cube in damaged.read() {
+        let before = ((cube.life + cube.damage) / quarter).floor();
+        if (cube.life / quarter).floor() < before {
+            shake.add_trauma(0.3);
+        }
+    }
+
+    for nut in destroyed.read() {
+        match nut.kind {
+            NutType::Gold => shake.add_trauma(0.3),
+            NutType::Diamant => {
+                shake.add_trauma(0.5);
+                shake.punch = 1.;
+            }
+            _ => {}
+        }
+    }
+
+    for _ in lost.read() {
+        shake.add_trauma(0.6);
+    }
+}
+
+fn move_camera(
+    camera: Single<(&mut CameraShake, &mut Transform)>,
+    settings: Res<CameraSettings>,
+    time: Res<Time>,
+) {
+    let (mut shake, mut transform) = camera.into_inner();
+    let delta = time.delta_secs();
+    shake.time += delta;
+    shake.trauma = (shake.trauma - TRAUMA_DECAY * delta).max(0.);
+    shake.punch = (shake.punch - delta / PUNCH_TIME).max(0.);
+
+    let mut target = shake.rest;
+    if !settings.reduced_motion {
+        let amount = shake.trauma * shake.trauma * settings.shake;
+        // two waves per axis, so the camera wobbles instead of jumping from frame to frame
+        let t = shake.time * SHAKE_SPEED;
+        let wave = |phase: f32| ((t + phase).sin() + (t * 1.7 + phase).sin() * 0.5) / 1.5;
+        target.translation += Vec3::new(wave(0.), wave(2.1), 0.) * amount * MAX_OFFSET;
+        target.rotate_z(wave(4.2) * amount * MAX_ANGLE);
+        target.scale *= 1. - PUNCH_ZOOM * shake.punch * shake.punch * settings.shake;
+    }
+
+    transform.set_if_neq(target);
+}
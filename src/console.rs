@@ -0,0 +1,266 @@
+use bevy::{
+    input::keyboard::{Key, KeyboardInput},
+    prelude::*,
+};
+
+use crate::{
+    GameState, Money, NutType, PlayerStats,
+    forest::{Cube, PlayerCube, SpawnNutMessage},
+    rng::SeedInputSystems,
+};
+
+/// Lines of output the console keeps
+const CONSOLE_LINES: usize = 8;
+
+const HELP: &str =
+    "money <amount> | spawn <kind> <x> <y> | stat <name> <value> | state <title|play|shop> | kill";
+
+/// Developer console for cheats while testing, toggled with the key left of 1
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Console>()
+            .add_message::<ConsoleCommand>()
+            // a typed command is not a seed
+            .configure_sets(
+                Update,
+                SeedInputSystems.run_if(|console: Res<Console>| !console.open),
+            )
+            .add_systems(Startup, setup_console)
+            .add_systems(
+                Update,
+                (
+                    toggle_console,
+                    type_command,
+                    run_commands,
+                    run_round_commands,
+                    update_console,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+struct Console {
+    open: bool,
+    input: String,
+    output: Vec<String>,
+}
+
+impl Console {
+    fn print(&mut self, line: impl Into<String>) {
+        self.output.push(line.into());
+        let overflow = self.output.len().saturating_sub(CONSOLE_LINES);
+        self.output.drain(..overflow);
+    }
+}
+
+#[derive(Message, Debug, Clone, PartialEq)]
+pub enum ConsoleCommand {
+    /// Adds money, negative to take some away
+    Money(i32),
+    /// A nut of the kind at the position
+    Spawn(NutType, Vec2),
+    /// Sets a field of the [`PlayerStats`] by its name
+    Stat(String, f64),
+    State(GameState),
+    /// Melts the player cube like the laser does, the round ends without a cube left
+    Kill,
+    Help,
+}
+
+impl ConsoleCommand {
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let number = |i: usize| -> Result<f64, String> {
+            let word = words.get(i).ok_or("Missing a number")?;
+            word.parse()
+                .map_err(|_| format!("'{}' is not a number", word))
+        };
+
+        match words.as_slice() {
+            ["money", _] => Ok(Self::Money(number(1)? as i32)),
+            ["spawn", kind, _, _] => {
+                let kind = match *kind {
+                    "base" => NutType::Base,
+                    "bronze" => NutType::Bronze,
+                    "silver" => NutType::Silver,
+                    "gold" => NutType::Gold,
+                    "diamant" | "diamond" => NutType::Diamant,
+                    _ => return Err(format!("Unknown nut '{}'", kind)),
+                };
+                let pos = Vec2::new(number(2)? as f32, number(3)? as f32);
+                Ok(Self::Spawn(kind, pos))
+            }
+            ["stat", name, _] => Ok(Self::Stat(name.to_string(), number(2)?)),
+            ["state", state] => GameState::from_name(state)
+                .map(Self::State)
+                .ok_or_else(|| format!("Unknown state '{}'", state)),
+            ["kill"] => Ok(Self::Kill),
+            ["help"] => Ok(Self::Help),
+            _ => Err(format!("Unknown command '{}', try 'help'", line.trim())),
+        }
+    }
+}
+
+#[derive(Debug, Component)]
+struct ConsoleRoot;
+
+#[derive(Debug, Component)]
+struct ConsoleText;
+
+fn setup_console(mut commands: Commands) {
+    commands
+        .spawn((
+            ConsoleRoot,
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(0.),
+                width: Val::Percent(100.),
+                padding: UiRect::all(Val::Px(8.)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0., 0., 0., 0.8)),
+            GlobalZIndex(101),
+            Visibility::Hidden,
+        ))
+        .with_child((ConsoleText, Text::new(""), TextFont::from_font_size(14.)));
+}
+
+fn toggle_console(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut console: ResMut<Console>,
+    mut root: Single<&mut Visibility, With<ConsoleRoot>>,
+) {
+    if !keys.just_pressed(KeyCode::Backquote) {
+        return;
+    }
+
+    console.open = !console.open;
+    console.input.clear();
+    **root = if console.open {
+        Visibility::Visible
+    } else {
+        Visibility::Hidden
+    };
+}
+
+fn type_command(
+    mut reader: MessageReader<KeyboardInput>,
+    mut console: ResMut<Console>,
+    mut writer: MessageWriter<ConsoleCommand>,
+) {
+    if !console.open {
+        reader.clear();
+        return;
+    }
+
+    for input in reader.read() {
+        if !input.state.is_pressed() || input.key_code == KeyCode::Backquote {
+            continue;
+        }
+
+        match &input.logical_key {
+            Key::Character(c) => console.input.push_str(c),
+            Key::Space => console.input.push(' '),
+            Key::Backspace => {
+                console.input.pop();
+            }
+            Key::Enter => {
+                let line = std::mem::take(&mut console.input);
+                console.print(format!("> {}", line));
+                match ConsoleCommand::parse(&line) {
+                    Ok(command) => {
+                        writer.write(command);
+                    }
+                    Err(err) => console.print(err),
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn run_commands(
+    mut reader: MessageReader<ConsoleCommand>,
+    mut console: ResMut<Console>,
+    mut money: ResMut<Money>,
+    mut stats: ResMut<PlayerStats>,
+    mut commands: Commands,
+) {
+    for command in reader.read() {
+        match command {
+            ConsoleCommand::Money(amount) => {
+                money.0 = (money.0 + amount).max(0);
+                console.print(format!("Money: {}", money.0));
+            }
+            ConsoleCommand::Stat(name, value) => match set_stat(&mut stats, name, *value) {
+                Ok(()) => console.print(format!("{} = {}", name, value)),
+                Err(err) => console.print(err),
+            },
+            ConsoleCommand::State(next) => commands.set_state(next.clone()),
+            ConsoleCommand::Help => console.print(HELP),
+            _ => {}
+        }
+    }
+}
+
+/// Commands that go through the forest, they only work in a round
+fn run_round_commands(
+    mut reader: MessageReader<ConsoleCommand>,
+    mut console: ResMut<Console>,
+    state: Res<State<GameState>>,
+    mut spawn_writer: MessageWriter<SpawnNutMessage>,
+    mut player: Query<&mut Cube, With<PlayerCube>>,
+) {
+    for command in reader.read() {
+        let playing = *state.get() == GameState::Playing;
+
+        match command {
+            ConsoleCommand::Spawn(kind, pos) if playing => {
+                spawn_writer.write(SpawnNutMessage(Some(*pos), *kind));
+            }
+            // the forest takes the melted cube like any other, with its messages and effects
+            ConsoleCommand::Kill if playing => {
+                for mut cube in &mut player {
+                    cube.life = 0.;
+                }
+            }
+            ConsoleCommand::Spawn(..) | ConsoleCommand::Kill => {
+                console.print("Only works in a round");
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Sets the field `name` through its serialized form, so every stat works without a list of names
+fn set_stat(stats: &mut PlayerStats, name: &str, value: f64) -> Result<(), String> {
+    let mut fields = serde_json::to_value(&*stats).map_err(|e| e.to_string())?;
+    let field = fields
+        .get_mut(name)
+        .ok_or_else(|| format!("Unknown stat '{}'", name))?;
+
+    *field = if field.is_i64() {
+        serde_json::Value::from(value as i64)
+    } else if field.is_f64() {
+        serde_json::Value::from(value)
+    } else {
+        return Err(format!("'{}' is not a number", name));
+    };
+
+    *stats = serde_json::from_value(fields).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn update_console(console: Res<Console>, mut text: Single<&mut Text, With<ConsoleText>>) {
+    if !console.is_changed() {
+        return;
+    }
+
+    let mut lines = console.output.clone();
+    lines.push(format!("> {}_", console.input));
+    text.0 = lines.join("\n");
+}
@@ -0,0 +1,293 @@
+use bevy::{
+    diagnostic::{DiagnosticsStore, EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin},
+    prelude::*,
+};
+
+use crate::{
+    Money, NutType, PlayerStats,
+    forest::{Cube, HALF_SIZE_CUBE, HALF_SIZE_SPAWN_FRAME, LaserPoints, PlayerCube, SimTransform},
+    laser,
+};
+
+/// Developer overlay, toggled with F3: hitboxes, laser hits, the spawn frame,
+/// FPS, entity counts and a panel to edit the stats live
+pub struct DebugPlugin;
+
+impl Plugin for DebugPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+            app.add_plugins(FrameTimeDiagnosticsPlugin::default());
+        }
+        if !app.is_plugin_added::<EntityCountDiagnosticsPlugin>() {
+            app.add_plugins(EntityCountDiagnosticsPlugin::default());
+        }
+
+        app.init_resource::<DebugOverlay>()
+            .add_systems(Startup, setup_debug_panel)
+            .add_systems(
+                Update,
+                (
+                    toggle_overlay,
+                    (
+                        draw_hitboxes,
+                        draw_laser_hits.run_if(resource_exists::<LaserPoints>),
+                        draw_spawn_frame,
+                        update_debug_info,
+                        (press_stat_buttons, update_stat_labels).chain(),
+                    )
+                        .run_if(overlay_enabled),
+                ),
+            );
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct DebugOverlay {
+    pub enabled: bool,
+}
+
+fn overlay_enabled(overlay: Res<DebugOverlay>) -> bool {
+    overlay.enabled
+}
+
+/// A value of the panel and how much one click changes it
+#[derive(Debug, Clone, Copy)]
+enum DebugStat {
+    Money,
+    Dmg,
+    LaserLength,
+    CubeMaxLife,
+    NutBaseLife,
+    NutValue,
+    RespawnNuts,
+    RespawnTime,
+    StartNuts,
+}
+
+impl DebugStat {
+    const ALL: [DebugStat; 9] = [
+        DebugStat::Money,
+        DebugStat::Dmg,
+        DebugStat::LaserLength,
+        DebugStat::CubeMaxLife,
+        DebugStat::NutBaseLife,
+        DebugStat::NutValue,
+        DebugStat::RespawnNuts,
+        DebugStat::RespawnTime,
+        DebugStat::StartNuts,
+    ];
+
+    fn label(&self, stats: &PlayerStats, money: &Money) -> String {
+        match self {
+            DebugStat::Money => format!("Money {}", money.0),
+            DebugStat::Dmg => format!("Damage {}", stats.dmg),
+            DebugStat::LaserLength => format!("Laser {}", stats.laser_length),
+            DebugStat::CubeMaxLife => format!("Cube Life {}", stats.cube_max_life),
+            DebugStat::NutBaseLife => format!("Nut Life {}", stats.nut_base_life),
+            DebugStat::NutValue => format!("Nut Worth {}", stats.base_nut_value),
+            DebugStat::RespawnNuts => format!("Respawn Nuts {}", stats.respawn_nuts),
+            DebugStat::RespawnTime => format!("Respawn Time {:.1}", stats.nuts_respawn_time),
+            DebugStat::StartNuts => format!("Start Nuts {}", stats.start_nuts),
+        }
+    }
+
+    /// Adds one step in the direction of `sign`
+    fn change(&self, sign: i32, stats: &mut PlayerStats, money: &mut Money) {
+        let step = sign as f32;
+        match self {
+            DebugStat::Money => money.0 = (money.0 + sign * 10).max(0),
+            DebugStat::Dmg => stats.dmg = (stats.dmg + step * 5.).max(0.),
+            DebugStat::LaserLength => {
+                stats.laser_length = (stats.laser_length + step * 25.).max(0.)
+            }
+            DebugStat::CubeMaxLife => {
+                stats.cube_max_life = (stats.cube_max_life + step * 20.).max(1.)
+            }
+            DebugStat::NutBaseLife => {
+                stats.nut_base_life = (stats.nut_base_life + step * 5.).max(1.)
+            }
+            DebugStat::NutValue => stats.base_nut_value = (stats.base_nut_value + sign).max(0),
+            DebugStat::RespawnNuts => stats.respawn_nuts = (stats.respawn_nuts + sign).max(0),
+            DebugStat::RespawnTime => {
+                stats.nuts_respawn_time = (stats.nuts_respawn_time + step * 0.2).max(0.2)
+            }
+            DebugStat::StartNuts => stats.start_nuts = (stats.start_nuts + sign).max(0),
+        }
+    }
+}
+
+#[derive(Debug, Component)]
+struct DebugPanel;
+
+#[derive(Debug, Component)]
+struct DebugInfoLabel;
+
+#[derive(Debug, Component)]
+struct DebugStatLabel(DebugStat);
+
+#[derive(Debug, Component)]
+struct DebugStatButton(DebugStat, i32);
+
+fn setup_debug_panel(mut commands: Commands) {
+    commands
+        .spawn((
+            DebugPanel,
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.),
+                left: Val::Px(10.),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.),
+                padding: UiRect::all(Val::Px(8.)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0., 0., 0., 0.7)),
+            GlobalZIndex(100),
+            Visibility::Hidden,
+        ))
+        .with_children(|panel| {
+            panel.spawn((DebugInfoLabel, Text::new(""), TextFont::from_font_size(14.)));
+
+            for stat in DebugStat::ALL {
+                panel
+                    .spawn(Node {
+                        align_items: AlignItems::Center,
+                        column_gap: Val::Px(6.),
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        for (sign, text) in [(-1, "-"), (1, "+")] {
+                            row.spawn((
+                                DebugStatButton(stat, sign),
+                                Button,
+                                Node {
+                                    width: Val::Px(20.),
+                                    height: Val::Px(20.),
+                                    justify_content: JustifyContent::Center,
+                                    align_items: AlignItems::Center,
+                                    ..default()
+                                },
+                                BackgroundColor(Color::srgb(0.2, 0.2, 0.3)),
+                            ))
+                            .with_child((Text::new(text), TextFont::from_font_size(14.)));
+                        }
+                        row.spawn((
+                            DebugStatLabel(stat),
+                            Text::new(""),
+                            TextFont::from_font_size(14.),
+                        ));
+                    });
+            }
+        });
+}
+
+fn toggle_overlay(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut overlay: ResMut<DebugOverlay>,
+    mut panel: Single<&mut Visibility, With<DebugPanel>>,
+) {
+    if !keys.just_pressed(KeyCode::F3) {
+        return;
+    }
+
+    overlay.enabled = !overlay.enabled;
+    **panel = if overlay.enabled {
+        Visibility::Visible
+    } else {
+        Visibility::Hidden
+    };
+}
+
+fn press_stat_buttons(
+    query: Query<(&Interaction, &DebugStatButton), Changed<Interaction>>,
+    mut stats: ResMut<PlayerStats>,
+    mut money: ResMut<Money>,
+) {
+    for (interaction, button) in &query {
+        if *interaction == Interaction::Pressed {
+            button.0.change(button.1, &mut stats, &mut money);
+        }
+    }
+}
+
+fn update_debug_info(
+    diagnostics: Res<DiagnosticsStore>,
+    cubes: Query<(), With<Cube>>,
+    nuts: Query<(), With<NutType>>,
+    mut info: Single<&mut Text, With<DebugInfoLabel>>,
+) {
+    let fps = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed())
+        .unwrap_or_default();
+    let entities = diagnostics
+        .get(&EntityCountDiagnosticsPlugin::ENTITY_COUNT)
+        .and_then(|count| count.value())
+        .unwrap_or_default();
+    info.0 = format!(
+        "FPS {:.0}\nEntities {}\nCubes {}\nNuts {}",
+        fps,
+        entities,
+        cubes.iter().count(),
+        nuts.iter().count()
+    );
+}
+
+fn update_stat_labels(
+    stats: Res<PlayerStats>,
+    money: Res<Money>,
+    mut labels: Query<(&DebugStatLabel, &mut Text)>,
+) {
+    for (label, mut text) in &mut labels {
+        text.0 = label.0.label(&stats, &money);
+    }
+}
+
+fn draw_hitboxes(mut gizmos: Gizmos, cubes: Query<(&Transform, Has<PlayerCube>), With<Cube>>) {
+    for (transform, is_player) in &cubes {
+        let (_, _, angle) = transform.rotation.to_euler(EulerRot::XYZ);
+        let color = if is_player {
+            Color::srgb(0.3, 0.8, 1.)
+        } else {
+            Color::srgb(1., 0.8, 0.2)
+        };
+        gizmos.rect_2d(
+            Isometry2d::new(transform.translation.truncate(), Rot2::radians(angle)),
+            Vec2::splat(HALF_SIZE_CUBE * 2.),
+            color,
+        );
+    }
+}
+
+/// Every laser segment and the normal of the face it ends on
+fn draw_laser_hits(
+    mut gizmos: Gizmos,
+    points: Res<LaserPoints>,
+    cubes: Query<&SimTransform, With<Cube>>,
+) {
+    for (start, end) in points.list.iter() {
+        gizmos.line_2d(*start, *end, Color::srgb(1., 0.2, 0.2));
+
+        // the segment ends on the face, look a bit further to hit it
+        let ray_end = *end + (*end - *start).normalize_or_zero();
+        for sim in &cubes {
+            let Some((hit, normal)) =
+                laser::hit_cube(*start, ray_end, &sim.current, HALF_SIZE_CUBE)
+            else {
+                continue;
+            };
+            if hit.distance(*end) < 1. {
+                gizmos.circle_2d(Isometry2d::from_translation(hit), 3., Color::WHITE);
+                gizmos.arrow_2d(hit, hit + normal * 30., Color::srgb(0.2, 1., 0.2));
+            }
+        }
+    }
+}
+
+fn draw_spawn_frame(mut gizmos: Gizmos) {
+    gizmos.rect_2d(
+        Isometry2d::IDENTITY,
+        HALF_SIZE_SPAWN_FRAME * 2.,
+        Color::srgb(0.6, 0.6, 0.6),
+    );
+}
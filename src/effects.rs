@@ -0,0 +1,266 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use rand::RngExt;
+
+use crate::{
+    GameState,
+    events::{CubeDamaged, NutDamaged, NutDestroyed},
+};
+
+/// Frames of `hit_sheet.png`
+const SPARK_FRAMES: usize = 2;
+/// Seconds between two sparks of the same burning cube
+const HIT_INTERVAL: f32 = 0.12;
+/// Seconds a hit spark lives
+const SPARK_TIME: f32 = 0.1;
+/// Seconds a burning cube flashes white after a spark
+const FLASH_TIME: f32 = 0.06;
+/// How far over white the flash tints the cube, the sprite clamps it to white
+const FLASH_BRIGHTNESS: f32 = 4.;
+const BURST_SPARKS: usize = 8;
+const BURST_TIME: f32 = 0.35;
+const BURST_SPEED: f32 = 140.;
+/// Finished effects kept for reuse, more than that are despawned
+const POOL_SIZE: usize = 64;
+/// Over the cubes and the laser
+const EFFECT_Z: f32 = 2.;
+
+/// Sparks where the laser burns, a white flash on the burning cube and a burst when a nut breaks
+pub struct EffectsPlugin;
+
+impl Plugin for EffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EffectPool>()
+            .add_systems(Startup, setup_effects)
+            .add_systems(OnExit(GameState::Playing), clear_effects)
+            .add_systems(
+                Update,
+                (
+                    spawn_hit_effects,
+                    spawn_burst_effects,
+                    animate_effects,
+                    flash_hits,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+#[derive(Debug, Resource)]
+struct EffectAssets {
+    sheet: Handle<Image>,
+    layout: Handle<TextureAtlasLayout>,
+}
+
+/// Hidden effect sprites, ready to be shown again
+#[derive(Debug, Resource, Default)]
+struct EffectPool(Vec<Entity>);
+
+/// A spark playing through the frames of the sheet, it goes back to the [`EffectPool`] at the end
+#[derive(Debug, Component)]
+struct Effect {
+    age: f32,
+    lifetime: f32,
+    velocity: Vec2,
+    /// Fades out over its lifetime
+    fade: bool,
+    color: Color,
+}
+
+impl Effect {
+    fn progress(&self) -> f32 {
+        (self.age / self.lifetime).min(1.)
+    }
+}
+
+/// On a cube the laser burns, seconds since its last spark
+#[derive(Debug, Component)]
+struct HitFlash(f32);
+
+fn setup_effects(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut layouts: ResMut<Assets<TextureAtlasLayout>>,
+) {
+    let layout =
+        TextureAtlasLayout::from_grid(UVec2::splat(32), SPARK_FRAMES as u32, 1, None, None);
+    commands.insert_resource(EffectAssets {
+        sheet: asset_server.load("embedded://hit_sheet.png"),
+        layout: layouts.add(layout),
+    });
+}
+
+/// Shows a pooled sprite with the effect, or spawns a new one if the pool is empty
+fn spawn_effect(
+    commands: &mut Commands,
+    pool: &mut EffectPool,
+    assets: &EffectAssets,
+    effect: Effect,
+    transform: Transform,
+) {
+    let sprite = Sprite {
+        image: assets.sheet.clone(),
+        texture_atlas: Some(TextureAtlas {
+            layout: assets.layout.clone(),
+            index: 0,
+        }),
+        color: effect.color,
+        ..Default::default()
+    };
+    let bundle = (effect, sprite, transform, Visibility::Inherited);
+
+    match pool.0.pop() {
+        Some(entity) => {
+            commands.entity(entity).insert(bundle);
+        }
+        None => {
+            commands.spawn(bundle);
+        }
+    }
+}
+
+fn spawn_hit_effects(
+    mut commands: Commands,
+    mut pool: ResMut<EffectPool>,
+    assets: Res<EffectAssets>,
+    mut nuts: MessageReader<NutDamaged>,
+    mut cubes: MessageReader<CubeDamaged>,
+    mut flashes: Query<&mut HitFlash>,
+) {
+    let hits = nuts
+        .read()
+        .map(|nut| (nut.entity, nut.hit_pos, Color::WHITE))
+        .chain(
+            cubes
+                .read()
+                .map(|cube| (cube.entity, cube.hit_pos, Color::srgb(0.7, 0.9, 1.))),
+        );
+
+    for (entity, pos, color) in hits {
+        match flashes.get_mut(entity) {
+            Ok(mut flash) if flash.0 >= HIT_INTERVAL => flash.0 = 0.,
+            Ok(_) => continue,
+            // the cube may be gone since the tick
+            Err(_) => {
+                commands.entity(entity).try_insert(HitFlash(0.));
+            }
+        }
+
+        let rotation = rand::rng().random_range(0. ..TAU);
+        spawn_effect(
+            &mut commands,
+            &mut pool,
+            &assets,
+            Effect {
+                age: 0.,
+                lifetime: SPARK_TIME,
+                velocity: Vec2::ZERO,
+                fade: false,
+                color,
+            },
+            Transform::from_translation(pos.extend(EFFECT_Z))
+                .with_rotation(Quat::from_rotation_z(rotation))
+                .with_scale(Vec3::splat(0.75)),
+        );
+    }
+}
+
+/// A ring of sparks in the color of the nut flies apart
+fn spawn_burst_effects(
+    mut commands: Commands,
+    mut pool: ResMut<EffectPool>,
+    assets: Res<EffectAssets>,
+    mut destroyed: MessageReader<NutDestroyed>,
+) {
+    for nut in destroyed.read() {
+        let offset = rand::rng().random_range(0. ..TAU);
+        for i in 0..BURST_SPARKS {
+            let angle = offset + i as f32 / BURST_SPARKS as f32 * TAU;
+            let direction = Vec2::from_angle(angle);
+            spawn_effect(
+                &mut commands,
+                &mut pool,
+                &assets,
+                Effect {
+                    age: 0.,
+                    lifetime: BURST_TIME,
+                    velocity: direction * BURST_SPEED,
+                    fade: true,
+                    color: nut.kind.color(),
+                },
+                Transform::from_translation((nut.pos + direction * 8.).extend(EFFECT_Z))
+                    .with_rotation(Quat::from_rotation_z(angle)),
+            );
+        }
+    }
+}
+
+fn animate_effects(
+    mut commands: Commands,
+    mut pool: ResMut<EffectPool>,
+    mut effects: Query<(Entity, &mut Effect, &mut Sprite, &mut Transform)>,
+    time: Res<Time>,
+) {
+    for (entity, mut effect, mut sprite, mut transform) in &mut effects {
+        effect.age += time.delta_secs();
+        if effect.age >= effect.lifetime {
+            release_effect(&mut commands, &mut pool, entity);
+            continue;
+        }
+
+        let progress = effect.progress();
+        transform.translation += (effect.velocity * time.delta_secs()).extend(0.);
+        if let Some(atlas) = &mut sprite.texture_atlas {
+            atlas.index = ((progress * SPARK_FRAMES as f32) as usize).min(SPARK_FRAMES - 1);
+        }
+        if effect.fade {
+            sprite.color = effect.color.with_alpha(1. - progress);
+        }
+    }
+}
+
+/// Hides the effect for the next one, or despawns it if the pool is full
+fn release_effect(commands: &mut Commands, pool: &mut EffectPool, entity: Entity) {
+    if pool.0.len() < POOL_SIZE {
+        commands
+            .entity(entity)
+            .remove::<Effect>()
+            .insert(Visibility::Hidden);
+        pool.0.push(entity);
+    } else {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// The burning cube and its ice flash white right after every spark
+fn flash_hits(
+    mut flashes: Query<(Entity, &mut HitFlash, Option<&Children>)>,
+    mut sprites: Query<&mut Sprite>,
+    time: Res<Time>,
+) {
+    for (entity, mut flash, children) in &mut flashes {
+        flash.0 += time.delta_secs();
+        let strength = (1. - flash.0 / FLASH_TIME).max(0.);
+        let brightness = 1. + (FLASH_BRIGHTNESS - 1.) * strength;
+        let color = Color::linear_rgb(brightness, brightness, brightness);
+
+        let children = children.into_iter().flat_map(|children| children.iter());
+        for sprite in [entity].into_iter().chain(children) {
+            if let Ok(mut sprite) = sprites.get_mut(sprite) {
+                sprite.color = color;
+            }
+        }
+    }
+}
+
+fn clear_effects(
+    mut commands: Commands,
+    mut pool: ResMut<EffectPool>,
+    effects: Query<Entity, With<Effect>>,
+) {
+    for entity in &effects {
+        release_effect(&mut commands, &mut pool, entity);
+    }
+}
@@ -0,0 +1,85 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{NutType, game_mode::GameMode};
+
+/// The laser took life from a nut in this tick
+#[derive(Debug, Clone, Message)]
+pub struct NutDamaged {
+    pub entity: Entity,
+    pub kind: NutType,
+    pub damage: f32,
+    /// Life left after the damage
+    pub life: f32,
+    pub pos: Vec2,
+    /// Where the laser meets the cube
+    pub hit_pos: Vec2,
+}
+
+/// A nut lost all its life and falls down, its value goes to the money
+#[derive(Debug, Clone, Message)]
+pub struct NutDestroyed {
+    pub kind: NutType,
+    pub value: i32,
+    pub pos: Vec2,
+}
+
+/// The laser took life from the player cube in this tick
+#[derive(Debug, Clone, Message)]
+pub struct CubeDamaged {
+    pub entity: Entity,
+    pub damage: f32,
+    /// Life left after the damage
+    pub life: f32,
+    pub pos: Vec2,
+    /// Where the laser meets the cube
+    pub hit_pos: Vec2,
+}
+
+/// A player cube is gone, the round goes on while cubes are left
+#[derive(Debug, Clone, Message)]
+pub struct CubeLost {
+    pub cause: CubeLossCause,
+    pub cubes_left: i32,
+    pub pos: Vec2,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CubeLossCause {
+    /// The laser took all its life
+    Melted,
+    /// The round timer ran out
+    TimeUp,
+}
+
+/// An upgrade was bought in the shop
+#[derive(Debug, Clone, Message)]
+pub struct UpgradePurchased {
+    pub id: usize,
+    pub title: String,
+    pub value_hint: String,
+    /// Money paid for it
+    pub cost: i32,
+    /// Times the upgrade is bought now
+    pub level: i32,
+}
+
+#[derive(Debug, Clone, Message)]
+pub struct RoundStarted {
+    pub mode: GameMode,
+    /// Seed of the round, see [`crate::rng::GameRng`]
+    pub seed: u64,
+}
+
+/// The end screen is over, the shop or the title screen comes next
+#[derive(Debug, Clone, Message)]
+pub struct RoundEnded {
+    pub mode: GameMode,
+    /// Money earned in the round
+    pub score: i32,
+    /// Money earned over every round of the run so far
+    pub run_score: i32,
+    /// The run score beats the best one of the mode
+    pub new_high_score: bool,
+}
@@ -1,16 +1,31 @@
 use std::{f32::consts::PI, time::Duration};
 
 use bevy::{
+    asset::RenderAssetUsages,
     math::{VectorSpace, ops::sin},
     prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
 };
 use rand::RngExt;
 
-use crate::{GameState, Money, NutType, PlayerStats};
+use crate::{
+    GameState, Money, NutType, PlayerStats,
+    audio::{AudioManager, Sound},
+    camera::CameraShake,
+    events::{
+        CubeDamaged, CubeLossCause, CubeLost, NutDamaged, NutDestroyed, RoundEnded, RoundStarted,
+    },
+    game_mode::{GameMode, HighScores, RoundScore, RunClock, RunScore},
+    laser,
+    rng::GameRng,
+    sprite_animation::{AnimationSheet, SpriteAnimation},
+};
 
-const HALF_SIZE_CUBE: f32 = 16.;
+pub const HALF_SIZE_CUBE: f32 = 16.;
+/// Where the laser starts, it points to the right
+pub const LASER_SOURCE: Vec2 = Vec2::new(-250., -200.);
 const GRAVITY: Vec2 = Vec2::new(0., 70.);
-const HALF_SIZE_SPAWN_FRAME: Vec2 = Vec2::new(300., 200.);
+pub const HALF_SIZE_SPAWN_FRAME: Vec2 = Vec2::new(300., 200.);
 
 pub struct ForestPlugin;
 
@@ -18,21 +33,72 @@ impl Plugin for ForestPlugin {
     fn build(&self, app: &mut App) {
         app.add_message::<SpawnNutMessage>()
             .add_message::<DeadPlayerMessage>()
+            .add_message::<NutDamaged>()
+            .add_message::<NutDestroyed>()
+            .add_message::<CubeDamaged>()
+            .add_message::<CubeLost>()
+            .add_message::<RoundStarted>()
+            .add_message::<RoundEnded>()
+            .init_resource::<CubeInput>()
+            .init_resource::<LaserHits>()
+            .init_resource::<RunScore>()
+            .init_resource::<RunClock>()
+            .configure_sets(
+                FixedUpdate,
+                (ForestSystems::Input, ForestSystems::Simulation)
+                    .chain()
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(round_running),
+            )
             .add_systems(OnEnter(GameState::Playing), setup_forest)
             .add_systems(
-                Update,
+                FixedUpdate,
                 (
+                    store_previous_transforms,
                     update_cube,
                     collide_laser_cube,
-                    draw_laser,
+                    tick_run_clock,
+                    update_round_timer,
                     update_respawn_nuts,
                     spawn_nuts,
-                    handle_sprite_state_nut,
-                    handle_sprite_state_player,
                     handle_falling,
                     handle_dead_cubes,
+                    collect_nuts,
                     on_dead,
                     check_end_timer,
+                )
+                    .chain()
+                    .in_set(ForestSystems::Simulation),
+            );
+    }
+}
+
+/// Sprites, sounds and mouse input of the forest, everything a headless simulation can leave out
+pub struct ForestRenderPlugin;
+
+impl Plugin for ForestRenderPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_atlas)
+            .add_systems(
+                OnEnter(GameState::Playing),
+                (setup_forest_render, start_laser_hum),
+            )
+            .add_systems(OnExit(GameState::Playing), stop_laser_hum)
+            .add_observer(add_player_sprite)
+            .add_observer(add_nut_sprite)
+            .add_observer(start_hit_sound)
+            .add_observer(stop_hit_sound)
+            .add_systems(
+                Update,
+                (
+                    read_cursor,
+                    interpolate_transforms,
+                    draw_laser,
+                    mark_laser_targets,
+                    place_hit_sounds,
+                    update_laser_hum,
+                    play_release_sounds,
+                    melt_ice,
                 )
                     .run_if(in_state(GameState::Playing))
                     .chain(),
@@ -40,18 +106,124 @@ impl Plugin for ForestPlugin {
     }
 }
 
+/// The forest runs on the fixed timestep, so the outcome does not depend on the frame rate
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ForestSystems {
+    /// Decides the [`CubeInput`] of the tick
+    Input,
+    /// Everything that moves, damages and spawns in the forest
+    Simulation,
+}
+
+/// Latest world position the player moved the cube to, taken by the next tick
+#[derive(Debug, Resource, Default)]
+pub struct CubeInput(pub Option<Vec2>);
+
+/// Transform of the simulation, the [`Transform`] only shows it interpolated between two ticks
+#[derive(Debug, Component, Clone, Copy)]
+pub struct SimTransform {
+    pub current: Transform,
+    pub previous: Transform,
+}
+
+impl SimTransform {
+    fn new(transform: Transform) -> Self {
+        Self {
+            current: transform,
+            previous: transform,
+        }
+    }
+}
+
+/// Length of the fade at the end of the beam
+const LASER_FADE: f32 = 40.;
+
+/// One layer of one piece of the beam, the sprites stay from frame to frame
+/// and are hidden while the beam has fewer pieces
 #[derive(Debug, Component)]
-struct Laser;
+struct LaserSprite {
+    piece: usize,
+    layer: LaserLayer,
+}
+
+/// The beam is drawn as a wide halo under the red beam under a bright core
+#[derive(Debug, Clone, Copy)]
+enum LaserLayer {
+    Halo,
+    Beam,
+    Core,
+}
+
+impl LaserLayer {
+    const ALL: [LaserLayer; 3] = [LaserLayer::Halo, LaserLayer::Beam, LaserLayer::Core];
+
+    fn thickness(&self) -> f32 {
+        match self {
+            LaserLayer::Halo => 16.,
+            LaserLayer::Beam => 5.,
+            LaserLayer::Core => 2.,
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            LaserLayer::Halo => Color::srgba(1., 0.3, 0.3, 0.25),
+            LaserLayer::Beam => Color::srgb(1., 0.5, 0.5),
+            LaserLayer::Core => Color::srgb(1., 0.95, 0.9),
+        }
+    }
+
+    /// Every layer over the one before, all of them under the cubes
+    fn z(&self) -> f32 {
+        match self {
+            LaserLayer::Halo => -0.03,
+            LaserLayer::Beam => -0.02,
+            LaserLayer::Core => -0.01,
+        }
+    }
+}
+
+/// A straight part of the beam, the last one fades out
+#[derive(Debug, Clone, Copy)]
+struct LaserPiece {
+    start: Vec2,
+    end: Vec2,
+    fade: bool,
+}
+
+/// White that fades out to the right, stretched over the end of the beam
+#[derive(Debug, Resource)]
+struct LaserFadeImage(Handle<Image>);
 
 #[derive(Debug, Component)]
-struct Cube {
+pub struct Cube {
     size: f32,
-    life: f32,
+    pub life: f32,
+    pub max_life: f32,
+}
+
+impl Cube {
+    /// Share of its life the cube already lost, from 0 to 1, a cube without life counts as lost
+    pub fn lost_life(&self) -> f32 {
+        if self.max_life <= 0. {
+            return 1.;
+        }
+        1. - (self.life / self.max_life).clamp(0., 1.)
+    }
 }
 
 #[derive(Debug, Component)]
 struct Nut;
 
+/// A cube of the simulation, the player or a nut
+type ForestCube<'a> = (
+    Entity,
+    &'a mut Cube,
+    &'a SimTransform,
+    Option<&'a mut PlayerCube>,
+    Option<&'a NutType>,
+);
+
 #[derive(Debug, Component)]
 struct Falling(Timer, Vec2);
 
@@ -61,107 +233,123 @@ struct EndScreenTimer(Timer);
 #[derive(Debug, Component)]
 struct RespawnNutsTimer(Timer);
 
+/// Ends the round when it runs out, shown as the remaining seconds
+#[derive(Debug, Component)]
+struct RoundTimer(Timer);
+
 #[derive(Debug, Component)]
-struct PlayerCube {
-    available_cubes: i32,
+pub struct PlayerCube {
+    pub available_cubes: i32,
 }
-#[derive(Resource)]
-struct HitCubeSound(Entity);
 
-#[derive(Resource)]
-struct HitNutSound(Entity);
+/// Cubes the laser touched in the last tick, with where the beam met them
+#[derive(Debug, Resource, Default)]
+struct LaserHits(Vec<(Entity, Vec2)>);
 
-#[derive(Resource)]
-struct ReleaseNutSound(Entity);
+/// On a cube while the laser burns it, its hit sound loops as long as any cube has it
+#[derive(Debug, Component)]
+struct LaserTarget;
 
 #[derive(Debug, Component)]
 struct IceAnimation;
 
 #[derive(Debug, Message)]
-pub struct SpawnNutMessage(Option<Vec2>);
+pub struct SpawnNutMessage(pub Option<Vec2>, pub NutType);
 
 #[derive(Debug, Message)]
 pub struct DeadPlayerMessage;
 
+/// Sheets of the melting ice, on the player cube and around every nut
 #[derive(Debug, Clone, Resource)]
-struct AnimationAtlasLayout(Handle<TextureAtlasLayout>);
+struct IceSheets {
+    player: Handle<AnimationSheet>,
+    nut: Handle<AnimationSheet>,
+}
 
 #[derive(Debug, Resource)]
 pub struct LaserPoints {
     pub source_start: Vec2,
     pub source_dir: Vec2,
     pub list: Vec<(Vec2, Vec2)>,
+    /// The list of the tick before, to draw the beam between two ticks
+    pub previous: Vec<(Vec2, Vec2)>,
+}
+
+impl LaserPoints {
+    /// The beam `t` of the way from the tick before to the last one, like the cubes are drawn.
+    /// A beam that got more or less segments in the last tick is shown as it is now
+    pub fn interpolated(&self, t: f32) -> Vec<(Vec2, Vec2)> {
+        if self.previous.len() != self.list.len() {
+            return self.list.clone();
+        }
+        self.previous
+            .iter()
+            .zip(&self.list)
+            .map(|((start0, end0), (start1, end1))| (start0.lerp(*start1, t), end0.lerp(*end1, t)))
+            .collect()
+    }
 }
 
-fn setup_forest(
+pub(crate) fn setup_forest(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
     mut writer: MessageWriter<SpawnNutMessage>,
+    mut started_writer: MessageWriter<RoundStarted>,
     player_stats: Res<PlayerStats>,
-    mut texture_atlases: ResMut<Assets<TextureAtlasLayout>>,
+    mode: Res<GameMode>,
+    mut rng: ResMut<GameRng>,
 ) {
-    // background sprite
-    {
-        commands.spawn((
-            DespawnOnExit(GameState::Playing),
-            Sprite::from_image(asset_server.load("embedded://bg.png")),
-            Transform::from_xyz(0., 0., -1.),
-        ));
-    }
+    let rules = mode.rules();
+    rng.start_round();
+    started_writer.write(RoundStarted {
+        mode: *mode,
+        seed: rng.seed,
+    });
+    commands.insert_resource(RoundScore(0));
 
     // init laser
     {
-        let start = Vec2::new(-250., -200.);
+        let start = LASER_SOURCE;
         let direction = Vec2::new(1., 0.);
+        let list = vec![(start, start + direction * player_stats.laser_length)];
         let points = LaserPoints {
             source_start: start,
             source_dir: direction,
-            list: vec![(start, start + direction * player_stats.laser_length)],
+            previous: list.clone(),
+            list,
         };
         commands.insert_resource(points);
     }
 
-    // create and insert atlas
-    let atlas = TextureAtlasLayout::from_grid(UVec2::splat(32), 5, 1, None, None);
-    let layout = texture_atlases.add(atlas);
-    commands.insert_resource(AnimationAtlasLayout(layout.clone()));
-
     // init cube
     {
-        let cube = asset_server.load("embedded://player_cube_sheet.png");
+        let transform = Transform {
+            rotation: Quat::from_rotation_z(30. / 180. * PI),
+            translation: Vec3::new(0., 0., 0.),
+            ..Default::default()
+        };
 
         commands.spawn((
-            Sprite {
-                image: cube.clone(),
-                texture_atlas: Some(TextureAtlas {
-                    layout: layout.clone(),
-                    index: 0,
-                }),
-                ..Default::default()
+            transform,
+            SimTransform::new(transform),
+            PlayerCube {
+                available_cubes: rules.cubes,
             },
-            Transform {
-                rotation: Quat::from_rotation_z(30. / 180. * PI),
-                translation: Vec3::new(0., 0., 0.),
-                ..Default::default()
-            },
-            PlayerCube { available_cubes: 1 },
             Cube {
                 size: HALF_SIZE_CUBE,
                 life: player_stats.cube_max_life,
+                max_life: player_stats.cube_max_life,
             },
-            IceAnimation,
             DespawnOnExit(GameState::Playing),
         ));
     }
 
     // spawn the fist nut - this will spawn every time on the same position
-    writer.write(SpawnNutMessage(Some(Vec2::ZERO)));
+    writer.write(SpawnNutMessage(Some(Vec2::ZERO), NutType::Base));
 
     // spawn beginning nuts
     {
-        println!("Player start nuts -> {}", player_stats.start_nuts);
         for _ in 0..player_stats.start_nuts {
-            writer.write(SpawnNutMessage(None));
+            writer.write(SpawnNutMessage(None, NutType::Base));
         }
     }
 
@@ -176,142 +364,254 @@ fn setup_forest(
         ));
     }
 
-    // spawn sound
-    {
-        // cube hit sound
-        let sound_entity = commands
-            .spawn((
-                AudioPlayer::new(asset_server.load("embedded://cube_hit.wav")),
-                PlaybackSettings::LOOP.paused(),
-                DespawnOnExit(GameState::Playing),
-            ))
-            .id();
-
-        commands.insert_resource(HitCubeSound(sound_entity));
+    // round timer of the timed mode
+    if let Some(round_time) = rules.round_time {
+        commands.spawn((
+            DespawnOnExit(GameState::Playing),
+            RoundTimer(Timer::from_seconds(round_time, TimerMode::Once)),
+            Text2d::new(format!("{:.0}", round_time)),
+            TextLayout::new(Justify::Center, LineBreak::NoWrap),
+            Transform::from_xyz(0., 330., 1.),
+        ));
+    }
+}
 
-        // nut hit sound
-        let sound_entity = commands
-            .spawn((
-                AudioPlayer::new(asset_server.load("embedded://nut_hit.wav")),
-                PlaybackSettings::LOOP.paused(),
-                DespawnOnExit(GameState::Playing),
-            ))
-            .id();
+fn setup_atlas(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    commands.insert_resource(IceSheets {
+        player: asset_server.load("embedded://player_cube.aseprite"),
+        nut: asset_server.load("embedded://ice_nut.aseprite"),
+    });
+
+    // the fade of the laser end
+    let width = 32;
+    let data = (0..width)
+        .flat_map(|x| {
+            let alpha = 255 - x * 255 / (width - 1);
+            [255, 255, 255, alpha as u8]
+        })
+        .collect();
+    let fade = Image::new(
+        Extent3d {
+            width,
+            height: 1,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    commands.insert_resource(LaserFadeImage(images.add(fade)));
+}
 
-        commands.insert_resource(HitNutSound(sound_entity));
+fn setup_forest_render(mut commands: Commands, asset_server: Res<AssetServer>) {
+    // background sprite
+    {
+        commands.spawn((
+            DespawnOnExit(GameState::Playing),
+            Sprite::from_image(asset_server.load("embedded://bg.png")),
+            Transform::from_xyz(0., 0., -1.),
+        ));
     }
 }
 
 fn spawn_nuts(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
     mut reader: MessageReader<SpawnNutMessage>,
     player_stats: Res<PlayerStats>,
-    atlas_layout: Res<AnimationAtlasLayout>,
+    mode: Res<GameMode>,
+    clock: Res<RunClock>,
+    mut rng: ResMut<GameRng>,
 ) {
-    let nut: Handle<Image> = asset_server.load("embedded://nut.png");
-    let ice: Handle<Image> = asset_server.load("embedded://ice_nut_sheet.png");
-    // TODO: Set position
-    // TODO: resize sprite
+    // nuts get tougher the longer the run goes in modes that scale the difficulty
+    let nut_life = player_stats.nut_base_life * (1. + mode.rules().nut_life_growth * clock.0);
 
     for new_nut_pos in reader.read() {
-        // TODO Random with a chance NutType
-        let base_nut = NutType::Base;
         let pos = match new_nut_pos.0 {
             Some(v) => v,
             None => {
-                let mut rng = rand::rng();
                 let x = rng.random_range(-HALF_SIZE_SPAWN_FRAME.x..HALF_SIZE_SPAWN_FRAME.x);
                 let y = rng.random_range(-HALF_SIZE_SPAWN_FRAME.y..HALF_SIZE_SPAWN_FRAME.y);
                 Vec2::new(x, y)
             }
         };
 
-        commands
-            .spawn((
-                Sprite::from_image(nut.clone()),
-                Cube {
-                    size: player_stats.size,
-                    life: player_stats.nut_base_life,
-                },
-                Transform {
-                    rotation: Quat::from_rotation_z(player_stats.dir.to_angle()),
-                    translation: pos.extend(0.),
-                    ..Default::default()
-                },
-                base_nut,
-                DespawnOnExit(GameState::Playing),
-            ))
-            .with_child((
-                IceAnimation,
-                Sprite {
-                    image: ice.clone(),
-                    texture_atlas: Some(TextureAtlas {
-                        layout: atlas_layout.0.clone(),
-                        index: 0,
-                    }),
-                    ..Default::default()
-                },
-                DespawnOnExit(GameState::Playing),
-            ));
+        let transform = Transform {
+            rotation: Quat::from_rotation_z(player_stats.dir.to_angle()),
+            translation: pos.extend(0.),
+            ..Default::default()
+        };
+
+        commands.spawn((
+            Cube {
+                size: player_stats.size,
+                life: nut_life,
+                max_life: nut_life,
+            },
+            transform,
+            SimTransform::new(transform),
+            new_nut_pos.1,
+            DespawnOnExit(GameState::Playing),
+        ));
     }
 }
 
-fn update_cube(
-    mut cube: Single<&mut Transform, With<PlayerCube>>,
+fn add_player_sprite(
+    add: On<Add, PlayerCube>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    sheets: Res<IceSheets>,
+) {
+    let cube = asset_server.load("embedded://player_cube.aseprite#image");
+
+    commands.entity(add.entity).insert((
+        Sprite::from_image(cube),
+        SpriteAnimation::new(sheets.player.clone(), "melt"),
+        IceAnimation,
+    ));
+}
+
+fn add_nut_sprite(
+    add: On<Add, NutType>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    sheets: Res<IceSheets>,
+) {
+    let nut: Handle<Image> = asset_server.load("embedded://nut.png");
+    let ice: Handle<Image> = asset_server.load("embedded://ice_nut.aseprite#image");
+    // TODO: resize sprite
+
+    commands
+        .entity(add.entity)
+        .insert(Sprite::from_image(nut))
+        .with_child((
+            IceAnimation,
+            Sprite::from_image(ice),
+            SpriteAnimation::new(sheets.nut.clone(), "melt"),
+            DespawnOnExit(GameState::Playing),
+        ));
+}
+
+fn read_cursor(
+    mut input: ResMut<CubeInput>,
     mut cursor_event: MessageReader<CursorMoved>,
-    camera: Single<(&Camera, &GlobalTransform)>,
-    time: Res<Time>,
+    camera: Single<(&Camera, &CameraShake)>,
 ) {
-    let (camera, camera_trans) = *camera;
+    let (camera, shake) = *camera;
+    // a shaking camera does not move the cube
+    let camera_trans = GlobalTransform::from(shake.rest);
     for cursor_moved in cursor_event.read() {
         let window_mouse_pos = cursor_moved.position;
 
-        if let Ok(world_pos) = camera.viewport_to_world_2d(camera_trans, window_mouse_pos) {
-            cube.translation = world_pos.extend(0.);
-            cube.rotate(Quat::from_rotation_z(sin(time.delta_secs())));
+        if let Ok(world_pos) = camera.viewport_to_world_2d(&camera_trans, window_mouse_pos) {
+            input.0 = Some(world_pos);
         }
     }
 }
 
+fn update_cube(
+    mut cube: Single<&mut SimTransform, With<PlayerCube>>,
+    mut input: ResMut<CubeInput>,
+    time: Res<Time>,
+) {
+    let Some(world_pos) = input.0.take() else {
+        return;
+    };
+    cube.current.translation = world_pos.extend(0.);
+    cube.current
+        .rotate(Quat::from_rotation_z(sin(time.delta_secs())));
+}
+
+fn store_previous_transforms(query: Query<&mut SimTransform>, mut points: ResMut<LaserPoints>) {
+    for mut sim in query {
+        sim.previous = sim.current;
+    }
+    points.previous = points.list.clone();
+}
+
+/// Show the simulation between the last two ticks
+fn interpolate_transforms(
+    query: Query<(&mut Transform, &SimTransform)>,
+    fixed_time: Res<Time<Fixed>>,
+) {
+    let t = fixed_time.overstep_fraction();
+    for (mut transform, sim) in query {
+        transform.translation = sim.previous.translation.lerp(sim.current.translation, t);
+        transform.rotation = sim.previous.rotation.slerp(sim.current.rotation, t);
+    }
+}
+
+/// The simulation stops once the end screen is over, until the state changes
+fn round_running(end_screens: Query<&EndScreenTimer>) -> bool {
+    end_screens.iter().all(|timer| !timer.0.is_finished())
+}
+
 fn update_respawn_nuts(
     mut timer: Single<&mut RespawnNutsTimer>,
     mut writer: MessageWriter<SpawnNutMessage>,
     time: Res<Time>,
     stats: Res<PlayerStats>,
+    mut rng: ResMut<GameRng>,
 ) {
     timer.0.tick(time.delta());
 
     if timer.0.just_finished() {
         for _ in 0..stats.respawn_nuts {
-            writer.write(SpawnNutMessage(None));
+            writer.write(SpawnNutMessage(None, NutType::roll(&mut **rng)));
         }
     }
 }
 
+fn tick_run_clock(mut clock: ResMut<RunClock>, time: Res<Time>) {
+    clock.0 += time.delta_secs();
+}
+
+fn update_round_timer(
+    single: Single<(&mut RoundTimer, &mut Text2d)>,
+    player: Query<(Entity, &PlayerCube, &SimTransform)>,
+    end_screens: Query<(), With<EndScreenTimer>>,
+    time: Res<Time>,
+    money: Res<Money>,
+    mut lost_writer: MessageWriter<CubeLost>,
+    mut commands: Commands,
+) {
+    let (mut timer, mut text) = single.into_inner();
+    timer.0.tick(time.delta());
+    text.0 = format!("{:.0}", timer.0.remaining_secs().ceil());
+
+    if !timer.0.just_finished() || !end_screens.is_empty() {
+        return;
+    }
+
+    // time is up - take the cube out of the game like on a death
+    for (entity, player, sim) in &player {
+        lost_writer.write(CubeLost {
+            cause: CubeLossCause::TimeUp,
+            cubes_left: player.available_cubes - 1,
+            pos: sim.current.translation.truncate(),
+        });
+        commands.entity(entity).despawn();
+    }
+    spawn_end_screen(
+        &mut commands,
+        format!("Time's Up\nYou Have {} Nuts", money.0),
+    );
+}
+
 fn collide_laser_cube(
     mut points: ResMut<LaserPoints>,
-    mut cubes: Query<(
-        &mut Cube,
-        &Transform,
-        Option<&mut PlayerCube>,
-        Option<&NutType>,
-    )>,
+    mut cubes: Query<ForestCube>,
     time: Res<Time>,
     player_stats: Res<PlayerStats>,
-    cube_hit_sound: ResMut<HitCubeSound>,
-    nut_hit_sound: ResMut<HitNutSound>,
-    audio_sinks: Query<&AudioSink>,
+    mut hits: ResMut<LaserHits>,
+    mut nut_writer: MessageWriter<NutDamaged>,
+    mut cube_writer: MessageWriter<CubeDamaged>,
 ) {
-    // sound pause default
-    {
-        if let Ok(sink) = audio_sinks.get(cube_hit_sound.0) {
-            sink.pause();
-        }
-        if let Ok(sink) = audio_sinks.get(nut_hit_sound.0) {
-            sink.pause();
-        }
-    }
+    hits.0.clear();
 
     let start = points.source_start;
     let mut ray_start = start;
@@ -322,46 +622,40 @@ fn collide_laser_cube(
     points.list = vec![(start, start + ray_dir * remaining)];
 
     // First pass: find PlayerCube and reflect
-    for (mut cube, trans, is_player, is_nut_type) in cubes.iter_mut() {
-        let cube_matrix = trans.to_matrix();
-        let world_to_local = cube_matrix.inverse();
-
+    for (entity, mut cube, sim, is_player, is_nut_type) in cubes.iter_mut() {
         let ray_end = ray_start + ray_dir * remaining;
-        let local_start = world_to_local
-            .transform_point3(ray_start.extend(0.0))
-            .truncate();
-        let local_end = world_to_local
-            .transform_point3(ray_end.extend(0.0))
-            .truncate();
-
-        if let Some((hit_pos_local, local_normal)) =
-            ray_rect_intersection(local_start, local_end, HALF_SIZE_CUBE)
-        {
-            let hit_pos_world = cube_matrix
-                .transform_point3(hit_pos_local.extend(0.0))
-                .truncate();
 
-            cube.life -= time.delta_secs() * player_stats.dmg;
-
-            if is_nut_type.is_some() {
-                // play nut hit sound
-                if let Ok(sink) = audio_sinks.get(cube_hit_sound.0) {
-                    sink.play();
-                }
+        if let Some((hit_pos_world, world_normal)) =
+            laser::hit_cube(ray_start, ray_end, &sim.current, HALF_SIZE_CUBE)
+        {
+            let damage = time.delta_secs() * player_stats.dmg;
+            cube.life -= damage;
+            let pos = sim.current.translation.truncate();
+
+            hits.0.push((entity, hit_pos_world));
+            if let Some(kind) = is_nut_type {
+                nut_writer.write(NutDamaged {
+                    entity,
+                    kind: *kind,
+                    damage,
+                    life: cube.life,
+                    pos,
+                    hit_pos: hit_pos_world,
+                });
             }
 
             // handle player
             // TODO but change this later so that the laser can further s
             if is_player.is_some() {
-                // play hit sound
-                if let Ok(sink) = audio_sinks.get(cube_hit_sound.0) {
-                    sink.play();
-                }
+                cube_writer.write(CubeDamaged {
+                    entity,
+                    damage,
+                    life: cube.life,
+                    pos,
+                    hit_pos: hit_pos_world,
+                });
                 // Reflect the ray
-                let world_normal = (trans.rotation * local_normal.extend(0.0))
-                    .truncate()
-                    .normalize();
-                let reflect_dir = ray_dir - 2.0 * ray_dir.dot(world_normal) * world_normal;
+                let reflect_dir = laser::reflect(ray_dir, world_normal);
 
                 remaining -= (ray_start - hit_pos_world).length();
                 let reflect_end = hit_pos_world + reflect_dir * remaining;
@@ -379,163 +673,283 @@ fn collide_laser_cube(
     }
 }
 
+/// Moves the [`LaserTarget`] to the cubes the laser burns right now
+fn mark_laser_targets(
+    hits: Res<LaserHits>,
+    targets: Query<Entity, With<LaserTarget>>,
+    mut commands: Commands,
+) {
+    for entity in &targets {
+        if !hits.0.iter().any(|(hit, _)| *hit == entity) {
+            commands.entity(entity).remove::<LaserTarget>();
+        }
+    }
+    for (entity, _) in hits.0.iter() {
+        if !targets.contains(*entity) {
+            // the cube may be gone since the tick
+            commands.entity(*entity).try_insert(LaserTarget);
+        }
+    }
+}
+
+/// The hit sounds come from where the beam burns
+fn place_hit_sounds(
+    hits: Res<LaserHits>,
+    nuts: Query<(), With<NutType>>,
+    mut audio: ResMut<AudioManager>,
+) {
+    for (entity, pos) in hits.0.iter() {
+        audio.place_loop(hit_sound(*entity, &nuts), *pos);
+    }
+}
+
+fn hit_sound(entity: Entity, nuts: &Query<(), With<NutType>>) -> Sound {
+    if nuts.contains(entity) {
+        Sound::NutHit
+    } else {
+        Sound::CubeHit
+    }
+}
+
+fn start_hit_sound(
+    add: On<Add, LaserTarget>,
+    nuts: Query<(), With<NutType>>,
+    mut audio: ResMut<AudioManager>,
+) {
+    audio.start_loop(hit_sound(add.entity, &nuts));
+}
+
+fn stop_hit_sound(
+    remove: On<Remove, LaserTarget>,
+    nuts: Query<(), With<NutType>>,
+    mut audio: ResMut<AudioManager>,
+) {
+    audio.stop_loop(hit_sound(remove.entity, &nuts));
+}
+
+fn start_laser_hum(mut audio: ResMut<AudioManager>) {
+    audio.start_loop(Sound::LaserHum);
+}
+
+fn stop_laser_hum(mut audio: ResMut<AudioManager>) {
+    audio.stop_loop(Sound::LaserHum);
+}
+
+/// The hum rises as the beam gets shorter, reflects and burns a nut close to breaking,
+/// every new reflection pings
+fn update_laser_hum(
+    points: Res<LaserPoints>,
+    player_stats: Res<PlayerStats>,
+    burning_nuts: Query<&Cube, (With<LaserTarget>, With<NutType>)>,
+    mut audio: ResMut<AudioManager>,
+    mut last_reflections: Local<usize>,
+) {
+    let reflections = points.list.len().saturating_sub(1);
+    let length: f32 = points.list.iter().map(|(a, b)| a.distance(*b)).sum();
+    let shortened = 1. - (length / player_stats.laser_length).clamp(0., 1.);
+    // share of its life the burning nut already lost
+    let progress = burning_nuts.iter().map(Cube::lost_life).fold(0., f32::max);
+    let burning = !burning_nuts.is_empty();
+
+    let speed = 0.85 + 0.3 * shortened + 0.08 * reflections as f32 + 0.3 * progress;
+    let volume = 0.7 + 0.15 * reflections as f32 + if burning { 0.3 } else { 0. };
+    audio.modulate_loop(Sound::LaserHum, speed, volume);
+    audio.modulate_loop(Sound::NutHit, 1. + 0.25 * progress, 1.);
+
+    if reflections > *last_reflections {
+        audio.play(Sound::ReflectStinger);
+    }
+    *last_reflections = reflections;
+}
+
+fn play_release_sounds(mut reader: MessageReader<NutDestroyed>, mut audio: ResMut<AudioManager>) {
+    for nut in reader.read() {
+        audio.play_at(Sound::NutRelease, nut.pos);
+        audio.play_at(Sound::KillStinger, nut.pos);
+    }
+}
+
 fn handle_dead_cubes(
-    query: Query<(Entity, &Cube, Option<&mut PlayerCube>, Option<&NutType>)>,
+    query: Query<ForestCube>,
     mut commands: Commands,
-    mut money: ResMut<Money>,
     player_stats: Res<PlayerStats>,
     mut writer: MessageWriter<DeadPlayerMessage>,
+    mut destroyed_writer: MessageWriter<NutDestroyed>,
+    mut lost_writer: MessageWriter<CubeLost>,
 ) {
-    for (entity, cube, player, nut_type) in query {
+    for (entity, mut cube, sim, player, nut_type) in query {
         if cube.life > 0. {
             continue;
         }
+        let pos = sim.current.translation.truncate();
 
         // when a nut has zero life
         if let Some(nut_type) = nut_type {
             commands.entity(entity).remove::<Cube>();
-            money.0 += player_stats.get_value(nut_type);
+            destroyed_writer.write(NutDestroyed {
+                kind: *nut_type,
+                value: player_stats.get_value(nut_type),
+                pos,
+            });
 
             commands.entity(entity).insert(Falling(
                 Timer::new(Duration::new(1, 0), TimerMode::Once),
                 Vec2::new(0., 0.),
             ));
-
-            println!("Nuts: {}", money.0);
             continue;
         }
 
         // when the player has zero life
         if let Some(mut _player) = player {
             _player.available_cubes -= 1;
+            lost_writer.write(CubeLost {
+                cause: CubeLossCause::Melted,
+                cubes_left: _player.available_cubes.max(0),
+                pos,
+            });
             if _player.available_cubes < 1 {
                 // when the player has zero cubes left
                 writer.write(DeadPlayerMessage);
                 commands.entity(entity).despawn();
+            } else {
+                // the next cube takes over
+                cube.life = cube.max_life;
             }
         }
     }
 }
 
-/// Returns the (intersection_point, normal) if the line hits the cube
-fn ray_rect_intersection(p0: Vec2, p1: Vec2, size: f32) -> Option<(Vec2, Vec2)> {
-    let d = p1 - p0;
-    let mut t_near = -f32::INFINITY;
-    let mut t_far = f32::INFINITY;
-    let mut normal = Vec2::ZERO;
-
-    for i in 0..2 {
-        if d[i].abs() < f32::EPSILON {
-            if p0[i].abs() > size {
-                return None;
-            }
-        } else {
-            let mut t1 = (-size - p0[i]) / d[i];
-            let mut t2 = (size - p0[i]) / d[i];
-            let mut n = if d[i] > 0.0 {
-                Vec2::new(
-                    if i == 0 { -1.0 } else { 0.0 },
-                    if i == 1 { -1.0 } else { 0.0 },
-                )
-            } else {
-                Vec2::new(
-                    if i == 0 { 1.0 } else { 0.0 },
-                    if i == 1 { 1.0 } else { 0.0 },
-                )
-            };
+/// The value of every destroyed nut goes to the money and the scores of the round and the run
+fn collect_nuts(
+    mut reader: MessageReader<NutDestroyed>,
+    mut money: ResMut<Money>,
+    mut score: ResMut<RoundScore>,
+    mut run_score: ResMut<RunScore>,
+) {
+    for nut in reader.read() {
+        money.0 += nut.value;
+        score.0 += nut.value;
+        run_score.0 += nut.value;
+    }
+}
 
-            if t1 > t2 {
-                std::mem::swap(&mut t1, &mut t2);
-                n *= -1.0;
-            }
-            if t1 > t_near {
-                t_near = t1;
-                normal = n;
-            }
-            t_far = t_far.min(t2);
+/// Splits the laser segments into pieces, the end of the last segment fades out
+fn laser_pieces(lines: &[(Vec2, Vec2)]) -> Vec<LaserPiece> {
+    let mut pieces = Vec::new();
+    for (i, (start, end)) in lines.iter().enumerate() {
+        let last = i + 1 == lines.len();
+        let length = start.distance(*end);
+        if !last || length <= LASER_FADE {
+            pieces.push(LaserPiece {
+                start: *start,
+                end: *end,
+                fade: last,
+            });
+            continue;
         }
-    }
 
-    if t_near <= t_far && t_near >= 0.0 && t_near <= 1.0 {
-        Some((p0 + d * t_near, normal))
-    } else {
-        None
+        let fade_start = end.lerp(*start, LASER_FADE / length);
+        pieces.push(LaserPiece {
+            start: *start,
+            end: fade_start,
+            fade: false,
+        });
+        pieces.push(LaserPiece {
+            start: fade_start,
+            end: *end,
+            fade: true,
+        });
     }
+    pieces
 }
 
-/// Draw the laser points
-fn draw_laser(
-    mut commands: Commands,
-    lines: Res<LaserPoints>,
-    old_lines: Query<Entity, With<Laser>>,
-) {
-    // Despawn ALL old lines FIRST, outside the segment loop
-    for line in &old_lines {
-        commands.entity(line).despawn();
+fn laser_sprite(piece: &LaserPiece, layer: LaserLayer, fade: &LaserFadeImage) -> Sprite {
+    let length = piece.start.distance(piece.end);
+    Sprite {
+        image: if piece.fade {
+            fade.0.clone()
+        } else {
+            Handle::default()
+        },
+        color: layer.color(),
+        custom_size: Some(Vec2::new(length, layer.thickness())),
+        ..Default::default()
     }
+}
 
-    for (start, end) in lines.list.iter() {
-        let thickness = 5.0;
-        let dir = end - start;
-        let center = start + (dir / 2.0);
-        let length = dir.length();
-        let angle = dir.y.atan2(dir.x);
-
-        commands.spawn((
-            Sprite {
-                color: Color::srgb(1., 0.5, 0.5),
-                custom_size: Some(Vec2::new(length, thickness)),
-                ..Default::default()
-            },
-            Transform {
-                translation: center.extend(0.),
-                rotation: Quat::from_rotation_z(angle),
-                ..Default::default()
-            },
-            Laser,
-            DespawnOnExit(GameState::Playing),
-        ));
+fn laser_transform(piece: &LaserPiece, layer: LaserLayer) -> Transform {
+    let dir = piece.end - piece.start;
+    Transform {
+        translation: (piece.start + dir / 2.).extend(layer.z()),
+        rotation: Quat::from_rotation_z(dir.to_angle()),
+        ..Default::default()
     }
 }
 
-fn handle_sprite_state_nut(
-    query: Query<(&ChildOf, &mut Sprite), With<IceAnimation>>,
-    parent_query: Query<&Cube>,
-    player_stats: Res<PlayerStats>,
+/// Moves the laser sprites onto the laser points, more sprites are only spawned
+/// when the beam has more pieces than ever before in the round
+fn draw_laser(
+    mut commands: Commands,
+    lines: Res<LaserPoints>,
+    fixed_time: Res<Time<Fixed>>,
+    fade: Res<LaserFadeImage>,
+    mut sprites: Query<(&LaserSprite, &mut Sprite, &mut Transform, &mut Visibility)>,
 ) {
-    for (child_of, mut sprite) in query {
-        let Ok(cube) = parent_query.get(child_of.parent()) else {
+    // the beam moves between the ticks like the cube it reflects on
+    let pieces = laser_pieces(&lines.interpolated(fixed_time.overstep_fraction()));
+
+    let mut pool_size = 0;
+    for (laser, mut sprite, mut transform, mut visibility) in &mut sprites {
+        pool_size = pool_size.max(laser.piece + 1);
+        let Some(piece) = pieces.get(laser.piece) else {
+            visibility.set_if_neq(Visibility::Hidden);
             continue;
         };
-        let state_part_value = player_stats.nut_base_life as usize / 4;
-        let state = (player_stats.nut_base_life - cube.life) as usize / (state_part_value);
-        let state = state.clamp(0, 4);
-        if let Some(_atlas) = &mut sprite.texture_atlas {
-            _atlas.index = state;
-            // println!("Atlas index: {}", state);
-        };
+
+        *sprite = laser_sprite(piece, laser.layer, &fade);
+        *transform = laser_transform(piece, laser.layer);
+        visibility.set_if_neq(Visibility::Inherited);
+    }
+
+    for (index, piece) in pieces.iter().enumerate().skip(pool_size) {
+        for layer in LaserLayer::ALL {
+            commands.spawn((
+                laser_sprite(piece, layer, &fade),
+                laser_transform(piece, layer),
+                LaserSprite {
+                    piece: index,
+                    layer,
+                },
+                DespawnOnExit(GameState::Playing),
+            ));
+        }
     }
 }
 
-fn handle_sprite_state_player(
-    single: Single<(&mut Sprite, &Cube), With<PlayerCube>>,
-    player_stats: Res<PlayerStats>,
+/// The ice shows how much of its cube is melted, the ice of a broken nut cracks apart
+fn melt_ice(
+    mut ice: Query<(Entity, Option<&ChildOf>, &mut SpriteAnimation), With<IceAnimation>>,
+    cubes: Query<&Cube>,
+    falling: Query<(), With<Falling>>,
 ) {
-    let (mut sprite, cube) = single.into_inner();
-    let state_part_value = player_stats.cube_max_life as usize / 4;
-    let state = (player_stats.cube_max_life - cube.life) as usize / (state_part_value);
-    let state = state.clamp(0, 4);
-    if let Some(_atlas) = &mut sprite.texture_atlas {
-        _atlas.index = state;
+    for (entity, child_of, mut animation) in &mut ice {
+        // the player cube is its own ice, a nut has it as a child
+        let owner = child_of.map_or(entity, ChildOf::parent);
+        if falling.contains(owner) {
+            animation.play("break");
+        } else if let Ok(cube) = cubes.get(owner) {
+            animation.play("melt");
+            animation.progress = cube.lost_life();
+        }
     }
 }
 
 fn handle_falling(
-    query: Query<(Entity, &mut Transform, &mut Falling)>,
+    query: Query<(Entity, &mut SimTransform, &mut Falling)>,
     time: Res<Time>,
     mut commands: Commands,
 ) {
-    for (entity, mut trans, mut falling) in query {
+    for (entity, mut sim, mut falling) in query {
         falling.0.tick(time.delta());
 
         if falling.0.is_finished() {
@@ -543,41 +957,73 @@ fn handle_falling(
             continue;
         }
         falling.1 += GRAVITY * time.delta_secs();
-        trans.translation -= falling.1.extend(0.);
+        sim.current.translation -= falling.1.extend(0.);
     }
 }
 
+fn spawn_end_screen(commands: &mut Commands, text: String) {
+    let timer = Timer::new(Duration::from_secs_f32(1.5), TimerMode::Once);
+
+    commands.spawn((
+        EndScreenTimer(timer),
+        Text2d::new(text),
+        TextLayout::new(Justify::Center, LineBreak::NoWrap),
+        Transform::from_xyz(0., 0., 0.),
+        DespawnOnExit(GameState::Playing),
+    ));
+}
+
 fn on_dead(
     mut reader: MessageReader<DeadPlayerMessage>,
     mut commands: Commands,
     money: Res<Money>,
+    end_screens: Query<(), With<EndScreenTimer>>,
 ) {
     for _ in reader.read() {
-        let timer = Timer::new(Duration::from_secs_f32(1.5), TimerMode::Once);
-        let text = format!("Game Over\nYou Have {} Nuts", money.0);
-
-        commands.spawn((
-            EndScreenTimer(timer),
-            Text2d::new(text),
-            TextLayout::new(Justify::Center, LineBreak::NoWrap),
-            Transform::from_xyz(0., 0., 0.),
-            DespawnOnExit(GameState::Playing),
-        ));
+        // the round may already be over by time
+        if !end_screens.is_empty() {
+            continue;
+        }
+        spawn_end_screen(
+            &mut commands,
+            format!("Game Over\nYou Have {} Nuts", money.0),
+        );
     }
 }
 
 fn check_end_timer(
     mut single: Single<&mut EndScreenTimer>,
     time: Res<Time>,
+    mode: Res<GameMode>,
+    (score, run_score): (Res<RoundScore>, Res<RunScore>),
+    mut high_scores: ResMut<HighScores>,
+    mut writer: MessageWriter<RoundEnded>,
     mut commands: Commands,
 ) {
     single.0.tick(time.delta());
 
-    if single.0.is_finished() {
+    if !single.0.just_finished() {
+        return;
+    }
+
+    // the run score only grows, so the best run is kept even if the player quits in the shop
+    let new_high_score = high_scores.submit(*mode, run_score.0);
+    if new_high_score {
+        info!("New {} high score: {}", mode.title(), run_score.0);
+    }
+    writer.write(RoundEnded {
+        mode: *mode,
+        score: score.0,
+        run_score: run_score.0,
+        new_high_score,
+    });
+
+    if mode.rules().shop {
         commands.set_state(GameState::Shoping);
+    } else {
+        // no shop - the run is over
+        commands.set_state(GameState::Start);
     }
 }
 
-// TODO Some kind of feedback by hitting the nut
-
 // Respawn timer
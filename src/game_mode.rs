@@ -0,0 +1,286 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    GameState, Money, PlayerStats, UpgradeList,
+    define_upgrades::get_upgrades,
+    rng::{GameRng, SeedSetting, daily_seed},
+};
+
+const MODE_BUTTON_SIZE: Vec2 = Vec2::new(150., 60.);
+/// File of the high scores if no other [`HighScoreStorage`] is inserted
+pub const HIGH_SCORE_FILE: &str = "highscores.txt";
+
+pub struct GameModePlugin;
+
+impl Plugin for GameModePlugin {
+    fn build(&self, app: &mut App) {
+        if !app.world().contains_resource::<HighScoreStorage>() {
+            app.init_resource::<HighScoreStorage>();
+        }
+        let high_scores = HighScores::load(app.world().resource::<HighScoreStorage>());
+
+        app.init_resource::<GameMode>()
+            .insert_resource(high_scores)
+            .add_systems(OnEnter(GameState::Start), setup_title)
+            .add_systems(Update, select_mode.run_if(in_state(GameState::Start)))
+            .add_systems(
+                Update,
+                save_high_scores
+                    .run_if(resource_changed::<HighScores>.and(not(resource_added::<HighScores>))),
+            );
+    }
+}
+
+/// The rule set the forest and the shop are played with
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum GameMode {
+    #[default]
+    Classic,
+    Timed,
+    Endless,
+    Hardcore,
+    /// Classic rules with a seed taken from the local date
+    Daily,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ModeRules {
+    /// Round ends after this many seconds, even if the cube is still alive
+    pub round_time: Option<f32>,
+    /// Cubes the player can lose before the round is over
+    pub cubes: i32,
+    /// Go to the shop between rounds, otherwise the run ends with the round
+    pub shop: bool,
+    /// Nut life grows by this factor per second of the run
+    pub nut_life_growth: f32,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 5] = [
+        GameMode::Classic,
+        GameMode::Timed,
+        GameMode::Endless,
+        GameMode::Hardcore,
+        GameMode::Daily,
+    ];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            GameMode::Classic => "Classic",
+            GameMode::Timed => "Timed",
+            GameMode::Endless => "Endless",
+            GameMode::Hardcore => "Hardcore",
+            GameMode::Daily => "Daily Challenge",
+        }
+    }
+
+    /// Finds the mode by its whole title in any case, e.g. `timed` or `daily challenge`
+    pub fn from_title(title: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|mode| mode.title().eq_ignore_ascii_case(title))
+    }
+
+    pub fn rules(&self) -> ModeRules {
+        match self {
+            GameMode::Classic | GameMode::Daily => ModeRules {
+                round_time: None,
+                cubes: 1,
+                shop: true,
+                nut_life_growth: 0.,
+            },
+            GameMode::Timed => ModeRules {
+                round_time: Some(60.),
+                cubes: 1,
+                shop: true,
+                nut_life_growth: 0.,
+            },
+            GameMode::Endless => ModeRules {
+                round_time: None,
+                cubes: 1,
+                shop: true,
+                nut_life_growth: 0.03,
+            },
+            GameMode::Hardcore => ModeRules {
+                round_time: None,
+                cubes: 1,
+                shop: false,
+                nut_life_growth: 0.,
+            },
+        }
+    }
+}
+
+/// Nuts collected in the running round
+#[derive(Resource, Debug, Default)]
+pub struct RoundScore(pub i32);
+
+/// Nuts collected over every round of the run, the shop keeps it
+#[derive(Resource, Debug, Default)]
+pub struct RunScore(pub i32);
+
+/// Seconds played in the forest over the whole run, nuts grow tougher with it in Endless
+#[derive(Resource, Debug, Default, Clone, Copy)]
+pub struct RunClock(pub f32);
+
+/// Where the [`HighScores`] are kept, insert it before the [`GameModePlugin`] to keep them elsewhere
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub enum HighScoreStorage {
+    /// One `Title=score` line per mode
+    File(PathBuf),
+    /// Only kept for the session, e.g. for tests
+    Memory,
+}
+
+impl Default for HighScoreStorage {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn default() -> Self {
+        HighScoreStorage::File(HIGH_SCORE_FILE.into())
+    }
+
+    // the browser build has no files to keep the scores in
+    #[cfg(target_arch = "wasm32")]
+    fn default() -> Self {
+        HighScoreStorage::Memory
+    }
+}
+
+/// Best run score for every mode
+#[derive(Resource, Debug, Default)]
+pub struct HighScores(HashMap<GameMode, i32>);
+
+impl HighScores {
+    pub fn get(&self, mode: GameMode) -> i32 {
+        self.0.get(&mode).copied().unwrap_or(0)
+    }
+
+    /// Returns true if the score is a new high score for the mode
+    pub fn submit(&mut self, mode: GameMode, score: i32) -> bool {
+        if score <= self.get(mode) {
+            return false;
+        }
+        self.0.insert(mode, score);
+        true
+    }
+
+    fn load(storage: &HighScoreStorage) -> Self {
+        let mut scores = HashMap::new();
+        let HighScoreStorage::File(path) = storage else {
+            return Self(scores);
+        };
+        let Ok(content) = std::fs::read_to_string(path) else {
+            return Self(scores);
+        };
+
+        for line in content.lines() {
+            let Some((title, score)) = line.split_once('=') else {
+                continue;
+            };
+            let mode = GameMode::from_title(title.trim());
+            if let (Some(mode), Ok(score)) = (mode, score.trim().parse()) {
+                scores.insert(mode, score);
+            }
+        }
+        Self(scores)
+    }
+
+    fn save(&self, storage: &HighScoreStorage) {
+        let HighScoreStorage::File(path) = storage else {
+            return;
+        };
+        let content: String = GameMode::ALL
+            .iter()
+            .map(|mode| format!("{}={}\n", mode.title(), self.get(*mode)))
+            .collect();
+        if let Err(err) = std::fs::write(path, content) {
+            warn!("Cannot save high scores to {}: {}", path.display(), err);
+        }
+    }
+}
+
+#[derive(Debug, Component)]
+struct ModeButton(GameMode);
+
+fn setup_title(mut commands: Commands, high_scores: Res<HighScores>) {
+    commands.spawn((
+        Text2d::new("Reflect The Laser\nWith You Slippery Ice Cupe\nTo Get The Nuts"),
+        TextLayout::new(Justify::Center, LineBreak::NoWrap),
+        Transform::from_xyz(0., 0., 0.),
+        DespawnOnExit(GameState::Start),
+    ));
+
+    commands
+        .spawn((
+            DespawnOnExit(GameState::Start),
+            Node {
+                width: Val::Percent(100.),
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(60.),
+                justify_content: JustifyContent::Center,
+                column_gap: Val::Px(25.),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            for mode in GameMode::ALL {
+                parent
+                    .spawn((
+                        ModeButton(mode),
+                        Button,
+                        Node {
+                            width: Val::Px(MODE_BUTTON_SIZE.x),
+                            height: Val::Px(MODE_BUTTON_SIZE.y),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgb(0.1, 0.2, 0.2)),
+                    ))
+                    .with_child((
+                        Text::new(format!("{}\nBest: {}", mode.title(), high_scores.get(mode))),
+                        TextFont::from_font_size(16.),
+                        TextLayout::new(Justify::Center, LineBreak::NoWrap),
+                    ));
+            }
+        });
+}
+
+fn save_high_scores(high_scores: Res<HighScores>, storage: Res<HighScoreStorage>) {
+    high_scores.save(&storage);
+}
+
+/// Resets money, stats and upgrades and enters the forest
+pub fn start_run(commands: &mut Commands, mode: GameMode, seed: u64) {
+    info!("Starting {} with seed {}", mode.title(), seed);
+
+    commands.insert_resource(GameRng::new(seed));
+    commands.insert_resource(mode);
+    commands.insert_resource(Money(0));
+    commands.insert_resource(RunScore(0));
+    commands.insert_resource(RunClock(0.));
+    commands.insert_resource(PlayerStats::default());
+    commands.insert_resource(UpgradeList(get_upgrades()));
+    commands.set_state(GameState::Playing);
+}
+
+/// Start a fresh run with the clicked mode
+fn select_mode(
+    query: Query<(&Interaction, &ModeButton), Changed<Interaction>>,
+    seed_setting: Res<SeedSetting>,
+    mut commands: Commands,
+) {
+    for (interaction, button) in &query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        let seed = match button.0 {
+            GameMode::Daily => daily_seed(),
+            _ => seed_setting.roll(),
+        };
+        start_run(&mut commands, button.0, seed);
+    }
+}
@@ -0,0 +1,264 @@
+use std::{
+    collections::HashMap,
+    f32::consts::{FRAC_PI_2, FRAC_PI_4, PI},
+    time::{Duration, Instant},
+};
+
+use bevy::{
+    app::ScheduleRunnerPlugin, log::LogPlugin, math::ops::sin, prelude::*,
+    state::app::StatesPlugin, time::TimeUpdateStrategy,
+};
+use rand::RngExt;
+
+use crate::{
+    GameState, Money, NutType, PlayerStats, UpgradeList, cli_arg,
+    define_upgrades::get_upgrades,
+    forest::{
+        Cube, CubeInput, ForestPlugin, ForestSystems, HALF_SIZE_CUBE, LaserPoints, PlayerCube,
+        SimTransform,
+    },
+    game_mode::{GameMode, HighScores, start_run},
+    rng::{GameRng, SeedSetting, seed_from_args},
+    shop::{BuyUpgradeMessage, PurchaseSystems, ShopPlugin},
+    telemetry::TelemetryPlugin,
+};
+
+/// The bot waits here, away from the laser, while it turns the cube
+const PARK_POS: Vec2 = Vec2::new(0., 300.);
+/// How far behind the nut the cube is put on the laser
+const AIM_OFFSET: f32 = 60.;
+
+/// Runs the game without a window: `--headless [--rounds 100] [--mode timed] [--seed 42] [--telemetry]`
+///
+/// A bot plays the forest and buys the cheapest upgrades in the shop.
+/// Every round and every upgrade is printed, to tune `define_upgrades` with data.
+pub fn run() {
+    let rounds = cli_arg("--rounds")
+        .and_then(|r| r.parse().ok())
+        .unwrap_or(100);
+    let mode = cli_arg("--mode")
+        .and_then(|m| GameMode::from_title(&m))
+        .unwrap_or_default();
+
+    // every update is exactly one tick of the forest
+    let tick = Time::<Fixed>::default().timestep();
+
+    // the log goes to stderr, the rounds printed by the bot stay alone on stdout
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::ZERO)),
+        StatesPlugin,
+        LogPlugin::default(),
+    ));
+    let seed = SeedSetting(seed_from_args()).roll();
+
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(tick))
+        .insert_resource(mode)
+        .insert_resource(GameRng::new(seed))
+        .insert_resource(Money(0))
+        .insert_resource(PlayerStats::default())
+        .insert_resource(UpgradeList(get_upgrades()))
+        .init_resource::<HighScores>()
+        .add_plugins((
+            ForestPlugin,
+            ShopPlugin,
+            TelemetryPlugin,
+            BotPlugin { rounds, seed },
+        ))
+        .insert_state(GameState::Playing)
+        .run();
+}
+
+struct BotPlugin {
+    rounds: usize,
+    seed: u64,
+}
+
+impl Plugin for BotPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SimStats {
+            rounds: self.rounds,
+            seed: self.seed,
+            started: Some(Instant::now()),
+            ..Default::default()
+        })
+        .add_systems(OnEnter(GameState::Playing), start_round)
+        .add_systems(OnExit(GameState::Playing), end_round)
+        .add_systems(FixedUpdate, bot_aim.in_set(ForestSystems::Input))
+        .add_systems(
+            FixedUpdate,
+            count_play_time.in_set(ForestSystems::Simulation),
+        )
+        .add_systems(
+            Update,
+            (
+                bot_shop
+                    .before(PurchaseSystems)
+                    .run_if(in_state(GameState::Shoping)),
+                report_upgrades
+                    .after(PurchaseSystems)
+                    .run_if(in_state(GameState::Shoping)),
+                restart_run.run_if(in_state(GameState::Start)),
+            ),
+        );
+    }
+}
+
+#[derive(Resource, Default)]
+struct SimStats {
+    rounds: usize,
+    seed: u64,
+    started: Option<Instant>,
+    round: usize,
+    /// Seconds played in the forest over all rounds
+    play_time: f32,
+    round_start_time: f32,
+    round_start_money: i32,
+    /// Bought levels of every upgrade id
+    levels: HashMap<usize, i32>,
+}
+
+fn start_round(mut stats: ResMut<SimStats>, money: Res<Money>) {
+    stats.round += 1;
+    stats.round_start_time = stats.play_time;
+    stats.round_start_money = money.0;
+}
+
+fn count_play_time(mut stats: ResMut<SimStats>, time: Res<Time>) {
+    stats.play_time += time.delta_secs();
+}
+
+fn end_round(stats: Res<SimStats>, money: Res<Money>, mut exit: MessageWriter<AppExit>) {
+    println!(
+        "round {:4} | {:6.1}s | earned {:8} | money {:8}",
+        stats.round,
+        stats.play_time - stats.round_start_time,
+        money.0 - stats.round_start_money,
+        money.0
+    );
+
+    if stats.round >= stats.rounds {
+        let real_time = stats.started.map(|s| s.elapsed().as_secs_f32());
+        println!(
+            "Simulated {} rounds with seed {} in {:.1}s of play time ({:.1}s real time)",
+            stats.round,
+            stats.seed,
+            stats.play_time,
+            real_time.unwrap_or_default()
+        );
+        exit.write(AppExit::Success);
+    }
+}
+
+/// Puts the cube on the laser so the reflection points to the closest nut.
+/// The cube only turns while it is moved, so it waits away from the laser until it is turned right.
+fn bot_aim(
+    mut input: ResMut<CubeInput>,
+    player: Single<&SimTransform, With<PlayerCube>>,
+    nuts: Query<&SimTransform, (With<NutType>, With<Cube>)>,
+    laser: Res<LaserPoints>,
+    stats: Res<PlayerStats>,
+    time: Res<Time>,
+) {
+    let source = laser.source_start;
+    let dir = laser.source_dir.normalize();
+    // the turn of one tick the cube is moved
+    let turn = sin(time.delta_secs());
+
+    // the nut with the shortest laser path, with the cube behind it on the laser
+    let target = nuts
+        .iter()
+        .map(|nut| {
+            let nut_pos = nut.current.translation.truncate();
+            let along = (nut_pos - source).dot(dir) + AIM_OFFSET;
+            let cube_pos = source + dir * along.max(AIM_OFFSET);
+            let path = (cube_pos - source).length() + (nut_pos - cube_pos).length();
+            (nut_pos, cube_pos, path)
+        })
+        .filter(|(_, _, path)| *path < stats.laser_length - HALF_SIZE_CUBE)
+        .min_by(|a, b| a.2.total_cmp(&b.2));
+
+    let Some((nut_pos, cube_pos, _)) = target else {
+        input.0 = Some(PARK_POS);
+        return;
+    };
+
+    // the face the laser hits has its normal at the angle `dir + PI + face`,
+    // which reflects the laser to `dir + PI + 2 * face`
+    let dir_angle = dir.to_angle();
+    let mut hit_pos = cube_pos - dir * HALF_SIZE_CUBE;
+    let mut face = 0.;
+    for _ in 0..2 {
+        let reflect_angle = (nut_pos - hit_pos).to_angle();
+        face = wrap_quarter((reflect_angle - dir_angle - PI) / 2.);
+        hit_pos = cube_pos - dir * HALF_SIZE_CUBE / face.cos();
+    }
+
+    let (_, _, rotation) = player.current.rotation.to_euler(EulerRot::XYZ);
+    let current_face = wrap_quarter(rotation - dir_angle - PI);
+    let on_laser = player.current.translation.truncate().distance(cube_pos) < 1.;
+
+    if on_laser && wrap_quarter(face - current_face).abs() < turn {
+        // in position - hold still
+        input.0 = None;
+    } else if wrap_quarter(face - current_face - turn).abs() < turn / 2. {
+        // the move itself turns the cube the last bit
+        input.0 = Some(cube_pos);
+    } else {
+        input.0 = Some(PARK_POS);
+    }
+}
+
+/// Wraps an angle of a square face into `-PI / 4..PI / 4`
+fn wrap_quarter(angle: f32) -> f32 {
+    (angle + FRAC_PI_4).rem_euclid(FRAC_PI_2) - FRAC_PI_4
+}
+
+/// Buys the cheapest upgrade it can afford, one per frame, then starts the next round
+fn bot_shop(
+    upgrades: Res<UpgradeList>,
+    money: Res<Money>,
+    mut writer: MessageWriter<BuyUpgradeMessage>,
+    mut commands: Commands,
+) {
+    let cheapest = upgrades
+        .0
+        .iter()
+        .filter(|u| u.cur_up_count < u.max_up_count && u.cost <= money.0)
+        .min_by_key(|u| u.cost);
+
+    match cheapest {
+        Some(upgrade) => {
+            writer.write(BuyUpgradeMessage {
+                upgrade_id: upgrade.id,
+            });
+        }
+        None => commands.set_state(GameState::Playing),
+    }
+}
+
+fn report_upgrades(upgrades: Res<UpgradeList>, mut stats: ResMut<SimStats>) {
+    for upgrade in upgrades.0.iter() {
+        let known = stats.levels.entry(upgrade.id).or_default();
+        if *known == upgrade.cur_up_count {
+            continue;
+        }
+        *known = upgrade.cur_up_count;
+
+        println!(
+            "  {:6.1}s round {:4}: {} {} ({}/{})",
+            stats.play_time,
+            stats.round,
+            upgrade.title,
+            upgrade.value_hint,
+            upgrade.cur_up_count,
+            upgrade.max_up_count
+        );
+    }
+}
+
+/// Modes without a shop end the run with the round, the bot starts over
+fn restart_run(mode: Res<GameMode>, mut rng: ResMut<GameRng>, mut commands: Commands) {
+    let seed = rng.random();
+    start_run(&mut commands, *mode, seed);
+}
@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+
+/// Where a laser from `start` to `end` hits a cube and the normal of the hit face, in world space
+pub fn hit_cube(start: Vec2, end: Vec2, cube: &Transform, half_size: f32) -> Option<(Vec2, Vec2)> {
+    let cube_matrix = cube.to_matrix();
+    let world_to_local = cube_matrix.inverse();
+
+    let local_start = world_to_local
+        .transform_point3(start.extend(0.0))
+        .truncate();
+    let local_end = world_to_local.transform_point3(end.extend(0.0)).truncate();
+
+    let (hit_pos_local, local_normal) = ray_rect_intersection(local_start, local_end, half_size)?;
+    let hit_pos = cube_matrix
+        .transform_point3(hit_pos_local.extend(0.0))
+        .truncate();
+    let normal = (cube.rotation * local_normal.extend(0.0))
+        .truncate()
+        .normalize();
+    Some((hit_pos, normal))
+}
+
+/// Direction of a laser after it is mirrored on a face with the `normal`
+pub fn reflect(dir: Vec2, normal: Vec2) -> Vec2 {
+    dir - 2.0 * dir.dot(normal) * normal
+}
+
+/// Returns the (intersection_point, normal) if the line hits the cube
+/// centered at the origin with the half `size`
+pub fn ray_rect_intersection(p0: Vec2, p1: Vec2, size: f32) -> Option<(Vec2, Vec2)> {
+    let d = p1 - p0;
+    let mut t_near = -f32::INFINITY;
+    let mut t_far = f32::INFINITY;
+    let mut normal = Vec2::ZERO;
+
+    for i in 0..2 {
+        if d[i].abs() < f32::EPSILON {
+            if p0[i].abs() > size {
+                return None;
+            }
+        } else {
+            let mut t1 = (-size - p0[i]) / d[i];
+            let mut t2 = (size - p0[i]) / d[i];
+            let mut n = if d[i] > 0.0 {
+                Vec2::new(
+                    if i == 0 { -1.0 } else { 0.0 },
+                    if i == 1 { -1.0 } else { 0.0 },
+                )
+            } else {
+                Vec2::new(
+                    if i == 0 { 1.0 } else { 0.0 },
+                    if i == 1 { 1.0 } else { 0.0 },
+                )
+            };
+
+            if t1 > t2 {
+                std::mem::swap(&mut t1, &mut t2);
+                n *= -1.0;
+            }
+            if t1 > t_near {
+                t_near = t1;
+                normal = n;
+            }
+            t_far = t_far.min(t2);
+        }
+    }
+
+    if t_near <= t_far && t_near >= 0.0 && t_near <= 1.0 {
+        Some((p0 + d * t_near, normal))
+    } else {
+        None
+    }
+}
@@ -0,0 +1,88 @@
+use bevy::{
+    camera::{ScalingMode, Viewport, visibility::RenderLayers},
+    prelude::*,
+    window::PrimaryWindow,
+};
+
+/// Size of the arena in world units, it fills as much of the window as it can
+/// and the rest of the window stays black
+pub const ARENA_SIZE: Vec2 = Vec2::new(1280., 720.);
+
+/// Scales the arena and the UI to the window, with black bars where the window
+/// is wider or taller than the arena
+pub struct LetterboxPlugin;
+
+impl Plugin for LetterboxPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<UiScale>()
+            .add_systems(Startup, spawn_bars_camera)
+            .add_systems(PreUpdate, fit_viewport);
+    }
+}
+
+/// Projection of the game camera, it always shows exactly the arena
+pub fn arena_projection() -> Projection {
+    Projection::Orthographic(OrthographicProjection {
+        scaling_mode: ScalingMode::Fixed {
+            width: ARENA_SIZE.x,
+            height: ARENA_SIZE.y,
+        },
+        ..OrthographicProjection::default_2d()
+    })
+}
+
+/// The viewport the arena takes in a window of that physical size, centered
+pub fn arena_viewport(window_size: UVec2) -> Viewport {
+    let scale = (window_size.as_vec2() / ARENA_SIZE).min_element();
+    let size = (ARENA_SIZE * scale)
+        .round()
+        .as_uvec2()
+        .min(window_size)
+        .max(UVec2::ONE);
+    Viewport {
+        physical_position: (window_size - size) / 2,
+        physical_size: size,
+        ..Default::default()
+    }
+}
+
+/// Renders nothing, only clears the window black under the game camera
+fn spawn_bars_camera(mut commands: Commands) {
+    commands.spawn((
+        Camera2d,
+        Camera {
+            order: -1,
+            clear_color: ClearColorConfig::Custom(Color::BLACK),
+            ..Default::default()
+        },
+        RenderLayers::none(),
+    ));
+}
+
+/// Before the cursor is read, so it is always mapped through the current viewport
+fn fit_viewport(
+    window: Single<&Window, With<PrimaryWindow>>,
+    mut camera: Single<&mut Camera, With<IsDefaultUiCamera>>,
+    mut ui_scale: ResMut<UiScale>,
+) {
+    let window_size = window.physical_size();
+    if window_size.x == 0 || window_size.y == 0 {
+        // minimized
+        return;
+    }
+
+    let viewport = arena_viewport(window_size);
+    // the UI is laid out for the arena size in logical pixels
+    let scale = viewport.physical_size.x as f32 / ARENA_SIZE.x / window.scale_factor();
+    if ui_scale.0 != scale {
+        ui_scale.0 = scale;
+    }
+
+    let changed = camera.viewport.as_ref().is_none_or(|current| {
+        current.physical_position != viewport.physical_position
+            || current.physical_size != viewport.physical_size
+    });
+    if changed {
+        camera.viewport = Some(viewport);
+    }
+}
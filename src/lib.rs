@@ -0,0 +1,382 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use bevy::{app::PluginGroupBuilder, prelude::*};
+use rand::RngExt;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    audio::GameAudioPlugin,
+    camera::{CameraEffectsPlugin, CameraShake},
+    define_upgrades::get_upgrades,
+    effects::EffectsPlugin,
+    forest::{ForestPlugin, ForestRenderPlugin},
+    game_mode::GameModePlugin,
+    letterbox::{LetterboxPlugin, arena_projection},
+    music::MusicPlugin,
+    options::OptionsPlugin,
+    particles::ParticlesPlugin,
+    popups::PopupPlugin,
+    replay::ReplayPlugin,
+    rng::RngPlugin,
+    shop::{ShopPlugin, ShopUiPlugin},
+    snow::SnowPlugin,
+    sprite_animation::SpriteAnimationPlugin,
+    telemetry::TelemetryPlugin,
+};
+
+pub mod aseprite;
+pub mod audio;
+pub mod camera;
+#[cfg(feature = "dev")]
+pub mod console;
+#[cfg(feature = "dev")]
+pub mod debug;
+pub mod define_upgrades;
+pub mod effects;
+pub mod events;
+pub mod forest;
+pub mod game_mode;
+pub mod headless;
+pub mod laser;
+pub mod letterbox;
+pub mod music;
+pub mod options;
+pub mod particles;
+pub mod popups;
+pub mod replay;
+pub mod report;
+pub mod rng;
+pub mod shop;
+pub mod snow;
+pub mod sprite_animation;
+pub mod telemetry;
+
+/// Every plugin of the game, add it after `DefaultPlugins`
+pub struct CozyWinterPlugin;
+
+impl PluginGroup for CozyWinterPlugin {
+    fn build(self) -> PluginGroupBuilder {
+        let group = PluginGroupBuilder::start::<Self>()
+            .add(GamePlugin)
+            .add(LetterboxPlugin)
+            .add(GameAudioPlugin)
+            .add(MusicPlugin)
+            .add(ForestPlugin)
+            .add(SpriteAnimationPlugin)
+            .add(ForestRenderPlugin)
+            .add(EffectsPlugin)
+            .add(PopupPlugin)
+            .add(CameraEffectsPlugin)
+            .add(ParticlesPlugin)
+            .add(SnowPlugin)
+            .add(ShopPlugin)
+            .add(ShopUiPlugin)
+            .add(GameModePlugin)
+            .add(RngPlugin)
+            .add(ReplayPlugin)
+            .add(OptionsPlugin)
+            .add(TelemetryPlugin);
+
+        #[cfg(feature = "dev")]
+        let group = group.add(debug::DebugPlugin).add(console::ConsolePlugin);
+
+        group
+    }
+}
+
+/// The camera, the resources of a run and the [`GameState`]
+pub struct GamePlugin;
+
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup)
+            .insert_state(GameState::Start);
+    }
+}
+
+impl PlayerStats {
+    /// Stats to start with instead of the default ones: `default`, `strong` or `max`,
+    /// `max` is about every upgrade bought to the end
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Self::default()),
+            "strong" => Some(Self {
+                dmg: 100.,
+                laser_length: 650.,
+                cube_max_life: 600.,
+                nut_base_life: 80.,
+                base_nut_value: 4,
+                nuts_respawn_time: 3.,
+                respawn_nuts: 3,
+                start_nuts: 3,
+                ..default()
+            }),
+            "max" => Some(Self {
+                dmg: 175.,
+                laser_length: 625.,
+                cube_max_life: 25600.,
+                nut_base_life: 75.,
+                base_nut_value: 512,
+                nuts_respawn_time: 2.,
+                respawn_nuts: 16,
+                start_nuts: 10,
+                ..default()
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn get_value(&self, nut_type: &NutType) -> i32 {
+        self.base_nut_value
+            * match nut_type {
+                NutType::Base => 1,
+                NutType::Bronze => 10,
+                NutType::Silver => 20,
+                NutType::Gold => 50,
+                NutType::Diamant => 100,
+            }
+    }
+
+    /// Value a spawned nut brings on average, with the chances of [`NutType::roll`]
+    pub fn average_value(&self) -> f32 {
+        let value: f32 = NutType::CHANCES
+            .iter()
+            .map(|(kind, chance)| self.get_value(kind) as f32 * *chance as f32)
+            .sum();
+        value / NutType::total_chance() as f32
+    }
+}
+
+// A global counter that starts at 0
+static UPGRADE_ID_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Clone)]
+pub struct UpgradeType {
+    pub id: usize,
+    pub cost: i32,
+    pub title: String,
+    pub value_hint: String,
+    pub cur_up_count: i32,
+    pub max_up_count: i32,
+    pub increase_value: fn(&mut Self, &mut PlayerStats, &mut Money),
+}
+
+impl UpgradeType {
+    // fn new() -> Self {
+    //     Self {
+    //         increase_value: |upgrade, stats, money| {
+    //             if upgrade.rise_up_count(money).is_some() {
+    //                 stats.dmg += 2.;
+    //                 upgrade.cost += 1;
+    //             }
+    //         },
+    //         ..Default::default()
+    //     }
+    // }
+
+    fn rise_up_count(&mut self, money: &mut Money) -> Option<()> {
+        if self.cur_up_count >= self.max_up_count {
+            return None;
+        }
+        if money.0 < self.cost {
+            return None;
+        }
+        money.0 -= self.cost;
+        self.cur_up_count += 1;
+        Some(())
+    }
+}
+
+impl Default for UpgradeType {
+    fn default() -> Self {
+        // fetch_add returns the PREVIOUS value and increments it by 1
+        let new_id = UPGRADE_ID_COUNTER.fetch_add(1, Ordering::Relaxed);
+
+        Self {
+            id: new_id,
+            title: "Undefined".into(),
+            value_hint: "+0".into(),
+            cost: 1,
+            cur_up_count: 0,
+            max_up_count: 5,
+            increase_value: |upgrade, stats, _money| {
+                // Note: Logic here usually depends on the specific upgrade type
+                stats.dmg += 2.0;
+                upgrade.cost += 1;
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component, Serialize, Deserialize)]
+pub enum NutType {
+    Base,
+    Bronze,
+    Silver,
+    Gold,
+    Diamant,
+}
+
+impl NutType {
+    /// How many nuts of a thousand spawned ones are of the kind
+    pub const CHANCES: [(NutType, u32); 5] = [
+        (NutType::Base, 950),
+        (NutType::Bronze, 35),
+        (NutType::Silver, 10),
+        (NutType::Gold, 4),
+        (NutType::Diamant, 1),
+    ];
+
+    /// A kind for a new nut, the more valuable the rarer
+    pub fn roll(rng: &mut impl RngExt) -> Self {
+        let mut roll = rng.random_range(0..Self::total_chance());
+        for (kind, chance) in Self::CHANCES {
+            if roll < chance {
+                return kind;
+            }
+            roll -= chance;
+        }
+        NutType::Base
+    }
+
+    fn total_chance() -> u32 {
+        Self::CHANCES.iter().map(|(_, chance)| chance).sum()
+    }
+
+    /// Color of the rarity, for the effects of the nut
+    pub fn color(&self) -> Color {
+        match self {
+            NutType::Base => Color::srgb(0.85, 0.7, 0.5),
+            NutType::Bronze => Color::srgb(0.85, 0.5, 0.25),
+            NutType::Silver => Color::srgb(0.8, 0.85, 0.9),
+            NutType::Gold => Color::srgb(1., 0.85, 0.3),
+            NutType::Diamant => Color::srgb(0.6, 0.95, 1.),
+        }
+    }
+}
+
+#[derive(Debug, Resource, Clone, Serialize, Deserialize)]
+pub struct PlayerStats {
+    pub dmg: f32,
+    pub laser_length: f32,
+    pub cube_max_life: f32,
+    pub size: f32,
+    pub nut_base_life: f32,
+    pub dir: Vec2,
+    pub base_nut_value: i32,
+    pub respawn_nuts: i32,
+    pub start_nuts: i32,
+    pub nuts_respawn_time: f32,
+}
+
+impl Default for PlayerStats {
+    fn default() -> Self {
+        Self {
+            dmg: 50.,
+            laser_length: 500.,
+            cube_max_life: 200.,
+            size: 16.,
+            dir: Vec2::new(0., 0.),
+            nut_base_life: 100.,
+            base_nut_value: 1,
+            nuts_respawn_time: 5.,
+            respawn_nuts: 1,
+            start_nuts: 0,
+        }
+    }
+}
+
+#[derive(Resource, Debug)]
+pub struct UpgradeList(pub Vec<UpgradeType>);
+
+#[derive(Debug, Resource, Clone)]
+pub struct Money(pub i32);
+
+#[derive(States, Debug, Eq, PartialEq, Hash, Clone)]
+pub enum GameState {
+    Start,
+    Playing,
+    Shoping,
+}
+
+impl GameState {
+    /// Parses the short name used by the options and the console, e.g. `play` or `shop`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "title" | "start" => Some(GameState::Start),
+            "play" | "playing" => Some(GameState::Playing),
+            "shop" => Some(GameState::Shoping),
+            _ => None,
+        }
+    }
+}
+
+/// Value following `name` on the command line, e.g. `--seed 42`
+#[cfg(not(target_arch = "wasm32"))]
+pub fn cli_arg(name: &str) -> Option<String> {
+    let mut args = std::env::args().skip_while(|a| a != name);
+    args.next()?;
+    args.next()
+}
+
+/// The browser build has no command line, `--seed` is read from the page URL as `?seed=42`
+#[cfg(target_arch = "wasm32")]
+pub fn cli_arg(name: &str) -> Option<String> {
+    let name = name.trim_start_matches('-');
+    url_params()
+        .into_iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value)
+        .filter(|value| !value.is_empty())
+}
+
+/// True if `name` is given on the command line, e.g. `--headless`
+#[cfg(not(target_arch = "wasm32"))]
+pub fn cli_flag(name: &str) -> bool {
+    std::env::args().any(|a| a == name)
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn cli_flag(name: &str) -> bool {
+    let name = name.trim_start_matches('-');
+    url_params().iter().any(|(key, _)| key == name)
+}
+
+/// Query parameters of the page, `?seed=42&fullscreen` gives `[("seed", "42"), ("fullscreen", "")]`
+#[cfg(target_arch = "wasm32")]
+fn url_params() -> Vec<(String, String)> {
+    let search = web_sys::window()
+        .and_then(|window| window.location().search().ok())
+        .unwrap_or_default();
+    search
+        .trim_start_matches('?')
+        .split('&')
+        .filter(|param| !param.is_empty())
+        .map(|param| match param.split_once('=') {
+            Some((key, value)) => (key.to_string(), value.to_string()),
+            None => (param.to_string(), String::new()),
+        })
+        .collect()
+}
+
+fn setup(mut commands: Commands) {
+    // the ears of the spatial sounds
+    commands.spawn((
+        Camera2d,
+        arena_projection(),
+        // the UI goes into the arena too
+        IsDefaultUiCamera,
+        CameraShake::new(Transform::default()),
+        SpatialListener::new(audio::EAR_GAP),
+    ));
+
+    // init money
+    let money = Money(0);
+    commands.insert_resource(money);
+
+    // init player
+    commands.insert_resource(PlayerStats::default());
+
+    let upgrades = get_upgrades();
+    commands.insert_resource(UpgradeList(upgrades));
+}
@@ -0,0 +1,378 @@
+use std::{f32::consts::TAU, sync::Arc, time::Duration};
+
+use bevy::{
+    audio::{AddAudioSource, Source, Volume},
+    prelude::*,
+    window::PrimaryWindow,
+};
+
+use crate::{
+    GameState, NutType,
+    audio::{AudioBus, AudioBuses},
+    events::{NutDestroyed, RoundEnded},
+};
+
+const SAMPLE_RATE: u32 = 22050;
+/// Seconds one track takes to fade into the next one
+const CROSSFADE_TIME: f32 = 1.5;
+/// Seconds the music stays quiet after a big moment
+const DUCK_TIME: f32 = 1.2;
+/// Share of the volume the music loses at the start of a duck
+const DUCK_DEPTH: f32 = 0.7;
+
+/// A looping track for every [`GameState`], crossfaded on every change
+pub struct MusicPlugin;
+
+impl Plugin for MusicPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_audio_source::<MusicTrack>()
+            .init_resource::<MusicDuck>()
+            .add_systems(Startup, setup_music)
+            .add_systems(
+                Update,
+                (
+                    pick_track.run_if(state_changed::<GameState>),
+                    duck_music,
+                    fade_channels,
+                    apply_music_volume,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Track {
+    Title,
+    Forest,
+    Shop,
+}
+
+impl Track {
+    const ALL: [Track; 3] = [Track::Title, Track::Forest, Track::Shop];
+
+    pub fn for_state(state: &GameState) -> Self {
+        match state {
+            GameState::Start => Track::Title,
+            GameState::Playing => Track::Forest,
+            GameState::Shoping => Track::Shop,
+        }
+    }
+
+    fn bpm(&self) -> f32 {
+        match self {
+            Track::Title => 70.,
+            Track::Forest => 100.,
+            Track::Shop => 90.,
+        }
+    }
+
+    /// The notes of two rounds through the chords
+    fn notes(&self) -> Vec<Note> {
+        let mut notes = Vec::new();
+
+        for (bar, chord) in CHORDS.iter().cycle().take(8).enumerate() {
+            let bar_start = bar as f32 * 4.;
+            let second_round = bar >= 4;
+
+            // a soft pad under every track
+            let pad_volume = if *self == Track::Forest { 0.5 } else { 1. };
+            for key in &chord[..3] {
+                notes.push(Note::new(Voice::Pad, bar_start, 4., *key, pad_volume));
+            }
+
+            match self {
+                Track::Title => {
+                    // a bell on every half bar, the second round an octave up
+                    for (i, key) in [chord[3], chord[1]].iter().enumerate() {
+                        let key = if second_round { key + 12 } else { *key };
+                        notes.push(Note::new(
+                            Voice::Bell,
+                            bar_start + i as f32 * 2.,
+                            2.,
+                            key + 12,
+                            1.,
+                        ));
+                    }
+                }
+                Track::Forest => {
+                    // plucked eighths up and down the chord
+                    for step in 0..8 {
+                        let index = [0, 1, 2, 3, 2, 1, 2, 3][step];
+                        let octave = if second_round && step % 4 == 3 { 12 } else { 0 };
+                        let key = chord[index] + octave;
+                        notes.push(Note::new(
+                            Voice::Pluck,
+                            bar_start + step as f32 * 0.5,
+                            0.5,
+                            key,
+                            1.,
+                        ));
+                    }
+                }
+                Track::Shop => {
+                    // a music box on quarters
+                    for step in 0..4 {
+                        let index = if second_round { 3 - step } else { step };
+                        notes.push(Note::new(
+                            Voice::Bell,
+                            bar_start + step as f32,
+                            1.,
+                            chord[index] + 24,
+                            0.6,
+                        ));
+                    }
+                }
+            }
+        }
+        notes
+    }
+
+    fn render(&self) -> MusicTrack {
+        let beat = 60. / self.bpm();
+        let length = (32. * beat * SAMPLE_RATE as f32) as usize;
+        let mut samples = vec![0f32; length];
+
+        for note in self.notes() {
+            let start = (note.start * beat * SAMPLE_RATE as f32) as usize;
+            let duration = note.length * beat;
+            let ring = duration + note.voice.release();
+            let frequency = 440. * 2f32.powf((note.key as f32 - 69.) / 12.);
+
+            for i in 0..(ring * SAMPLE_RATE as f32) as usize {
+                let t = i as f32 / SAMPLE_RATE as f32;
+                // the tail of the last notes rings into the start, so the loop has no seam
+                samples[(start + i) % length] +=
+                    note.voice.sample(frequency, t, duration) * note.volume;
+            }
+        }
+
+        MusicTrack {
+            samples: samples.into(),
+        }
+    }
+}
+
+/// Cmaj7, Am7, Fmaj7 and G6 as midi keys
+const CHORDS: [[i32; 4]; 4] = [
+    [60, 64, 67, 71],
+    [57, 60, 64, 67],
+    [53, 57, 60, 64],
+    [55, 59, 62, 64],
+];
+
+#[derive(Debug, Clone, Copy)]
+enum Voice {
+    Pad,
+    Bell,
+    Pluck,
+}
+
+impl Voice {
+    /// Seconds the voice rings after the note ends
+    fn release(&self) -> f32 {
+        match self {
+            Voice::Pad => 0.8,
+            Voice::Bell => 1.5,
+            Voice::Pluck => 0.3,
+        }
+    }
+
+    fn sample(&self, frequency: f32, t: f32, duration: f32) -> f32 {
+        let sine = |f: f32| (TAU * f * t).sin();
+        match self {
+            Voice::Pad => {
+                let attack = (t / 0.6).min(1.);
+                let release = if t > duration {
+                    1. - (t - duration) / self.release()
+                } else {
+                    1.
+                };
+                // two slightly detuned sines beat slowly against each other
+                (sine(frequency) + sine(frequency * 1.003)) * 0.5 * attack * release * 0.05
+            }
+            Voice::Bell => {
+                let decay = (-t * 3.).exp();
+                (sine(frequency) + sine(frequency * 2.76) * 0.3) * decay * 0.08
+            }
+            Voice::Pluck => {
+                let decay = (-t * 9.).exp();
+                (sine(frequency) + sine(frequency * 2.) * 0.4) * decay * 0.07
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Note {
+    voice: Voice,
+    /// Beat the note starts on
+    start: f32,
+    /// Length in beats
+    length: f32,
+    /// Midi key, 69 is A4
+    key: i32,
+    volume: f32,
+}
+
+impl Note {
+    fn new(voice: Voice, start: f32, length: f32, key: i32, volume: f32) -> Self {
+        Self {
+            voice,
+            start,
+            length,
+            key,
+            volume,
+        }
+    }
+}
+
+/// A track synthesized at startup, the game ships no music files
+#[derive(Asset, TypePath, Debug, Clone)]
+pub struct MusicTrack {
+    samples: Arc<[f32]>,
+}
+
+impl Decodable for MusicTrack {
+    type DecoderItem = f32;
+    type Decoder = MusicDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        MusicDecoder {
+            samples: self.samples.clone(),
+            index: 0,
+        }
+    }
+}
+
+pub struct MusicDecoder {
+    samples: Arc<[f32]>,
+    index: usize,
+}
+
+impl Iterator for MusicDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let sample = self.samples.get(self.index).copied();
+        self.index += 1;
+        sample
+    }
+}
+
+impl Source for MusicDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        Some(self.samples.len().saturating_sub(self.index))
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs_f32(
+            self.samples.len() as f32 / SAMPLE_RATE as f32,
+        ))
+    }
+}
+
+/// The player of one track, it pauses once it is faded out and goes on from there
+#[derive(Debug, Component)]
+pub struct MusicChannel {
+    pub track: Track,
+    /// Current share of the music volume, 0 to 1
+    pub level: f32,
+    /// Level the channel fades to
+    pub target: f32,
+}
+
+/// Time left of the duck
+#[derive(Debug, Resource, Default)]
+struct MusicDuck(f32);
+
+impl MusicDuck {
+    fn factor(&self) -> f32 {
+        let t = (self.0 / DUCK_TIME).clamp(0., 1.);
+        1. - DUCK_DEPTH * t * t
+    }
+}
+
+fn setup_music(mut commands: Commands, mut tracks: ResMut<Assets<MusicTrack>>) {
+    for track in Track::ALL {
+        let handle = tracks.add(track.render());
+        commands.spawn((
+            MusicChannel {
+                track,
+                level: 0.,
+                target: 0.,
+            },
+            AudioPlayer(handle),
+            PlaybackSettings::LOOP
+                .paused()
+                .with_volume(Volume::Linear(0.)),
+        ));
+    }
+}
+
+fn pick_track(state: Res<State<GameState>>, mut channels: Query<&mut MusicChannel>) {
+    let track = Track::for_state(state.get());
+    for mut channel in &mut channels {
+        channel.target = if channel.track == track { 1. } else { 0. };
+    }
+}
+
+/// Big moments take the music back for a moment
+fn duck_music(
+    mut duck: ResMut<MusicDuck>,
+    mut destroyed: MessageReader<NutDestroyed>,
+    mut ended: MessageReader<RoundEnded>,
+    time: Res<Time>,
+) {
+    let big_nut = destroyed
+        .read()
+        .any(|nut| matches!(nut.kind, NutType::Gold | NutType::Diamant));
+    let high_score = ended.read().any(|round| round.new_high_score);
+
+    if big_nut || high_score {
+        duck.0 = DUCK_TIME;
+    } else {
+        duck.0 = (duck.0 - time.delta_secs()).max(0.);
+    }
+}
+
+fn fade_channels(mut channels: Query<&mut MusicChannel>, time: Res<Time>) {
+    let step = time.delta_secs() / CROSSFADE_TIME;
+    for mut channel in &mut channels {
+        channel.level = if channel.level < channel.target {
+            (channel.level + step).min(channel.target)
+        } else {
+            (channel.level - step).max(channel.target)
+        };
+    }
+}
+
+fn apply_music_volume(
+    mut channels: Query<(&MusicChannel, &mut AudioSink)>,
+    buses: Res<AudioBuses>,
+    duck: Res<MusicDuck>,
+    window: Query<&Window, With<PrimaryWindow>>,
+) {
+    // the music holds still while the game is in the background
+    let focused = window.iter().all(|window| window.focused);
+
+    for (channel, mut sink) in &mut channels {
+        let audible = focused && channel.level > 0.;
+        if audible && sink.is_paused() {
+            sink.play();
+        } else if !audible && !sink.is_paused() {
+            sink.pause();
+        }
+
+        let volume = channel.level * duck.factor() * buses.volume(AudioBus::Music);
+        sink.set_volume(Volume::Linear(volume));
+    }
+}
@@ -0,0 +1,269 @@
+use bevy::{
+    prelude::*,
+    window::{MonitorSelection, WindowMode, WindowResolution},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    GameState, Money, PlayerStats, UpgradeList, cli_arg, cli_flag,
+    define_upgrades::get_upgrades,
+    forest::setup_forest,
+    game_mode::{GameMode, start_run},
+    rng::SeedSetting,
+};
+
+/// Folder of the save slots, one json file per slot
+#[cfg(not(target_arch = "wasm32"))]
+const SAVE_DIR: &str = "saves";
+
+/// Applies the [`LaunchOptions`] and keeps the save slot up to date
+pub struct OptionsPlugin;
+
+impl Plugin for OptionsPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.world().contains_resource::<LaunchOptions>() {
+            app.insert_resource(LaunchOptions::from_args());
+        }
+
+        // after the run resources of the game setup are in place
+        app.add_systems(PostStartup, skip_title)
+            .add_systems(
+                OnEnter(GameState::Playing),
+                // the forest is set up with the money and stats of the options
+                apply_run_options
+                    .run_if(resource_exists::<PendingRunOptions>)
+                    .before(setup_forest),
+            )
+            .add_systems(
+                OnEnter(GameState::Shoping),
+                save_slot.run_if(|options: Res<LaunchOptions>| options.slot.is_some()),
+            );
+    }
+}
+
+/// Options from the command line, e.g.
+/// `--state shop --mode timed --money 500 --preset strong --width 1600 --height 900 --fullscreen --slot test`.
+/// The browser build reads them from the page URL instead: `?state=shop&money=500&fullscreen`.
+/// The seed is read by the [`SeedSetting`] with `--seed`.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct LaunchOptions {
+    /// Skips the title screen straight into a round or the shop
+    pub state: Option<GameState>,
+    /// Mode of a run that skips the title screen, Classic if not given
+    pub mode: Option<GameMode>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub fullscreen: bool,
+    /// Money at the start of the first round
+    pub money: Option<i32>,
+    /// Name of the save slot the run is loaded from and saved to in every shop visit
+    pub slot: Option<String>,
+    /// Name of the [`PlayerStats::preset`] the first round starts with
+    pub preset: Option<String>,
+}
+
+impl LaunchOptions {
+    pub fn from_args() -> Self {
+        let state = cli_arg("--state").and_then(|name| {
+            let state = GameState::from_name(&name);
+            if state.is_none() {
+                warn!("Unknown state '{}'", name);
+            }
+            state
+        });
+        let mode = cli_arg("--mode").and_then(|title| {
+            let mode = GameMode::from_title(&title);
+            if mode.is_none() {
+                warn!("Unknown mode '{}'", title);
+            }
+            mode
+        });
+        let preset = cli_arg("--preset").filter(|name| {
+            let known = PlayerStats::preset(name).is_some();
+            if !known {
+                warn!("Unknown stats preset '{}'", name);
+            }
+            known
+        });
+
+        Self {
+            state,
+            mode,
+            width: number_arg("--width"),
+            height: number_arg("--height"),
+            fullscreen: cli_flag("--fullscreen"),
+            money: number_arg("--money"),
+            slot: cli_arg("--slot"),
+            preset,
+        }
+    }
+
+    /// Sets the resolution and the fullscreen mode of the game window
+    pub fn apply_to_window(&self, window: &mut Window) {
+        if self.width.is_some() || self.height.is_some() {
+            let width = self.width.unwrap_or(window.resolution.width() as u32);
+            let height = self.height.unwrap_or(window.resolution.height() as u32);
+            window.resolution = WindowResolution::new(width, height);
+        }
+        if self.fullscreen {
+            window.mode = WindowMode::BorderlessFullscreen(MonitorSelection::Current);
+        }
+    }
+}
+
+fn number_arg<T: std::str::FromStr>(name: &str) -> Option<T> {
+    let value = cli_arg(name)?;
+    match value.parse() {
+        Ok(value) => Some(value),
+        Err(_) => {
+            warn!("Invalid number '{}' for {}", value, name);
+            None
+        }
+    }
+}
+
+/// Money and stats of the options, waiting for the first round because a new run resets them
+#[derive(Resource, Debug)]
+pub(crate) struct PendingRunOptions {
+    money: Option<i32>,
+    stats: Option<PlayerStats>,
+}
+
+/// A run saved in the shop, loaded with `--slot <name>`
+#[derive(Debug, Serialize, Deserialize)]
+struct SaveSlot {
+    mode: GameMode,
+    money: i32,
+    stats: PlayerStats,
+    upgrades: Vec<SavedUpgrade>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedUpgrade {
+    count: i32,
+    cost: i32,
+}
+
+impl SaveSlot {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load(name: &str) -> Result<Self, String> {
+        let path = format!("{}/{}.json", SAVE_DIR, name);
+        let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&content).map_err(|e| e.to_string())
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save(&self, name: &str) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::create_dir_all(SAVE_DIR).map_err(|e| e.to_string())?;
+        std::fs::write(format!("{}/{}.json", SAVE_DIR, name), content).map_err(|e| e.to_string())
+    }
+
+    // the browser build has no files to keep a slot in
+    #[cfg(target_arch = "wasm32")]
+    fn load(_name: &str) -> Result<Self, String> {
+        Err("Save slots are not supported in the browser".into())
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn save(&self, _name: &str) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Starts the run right away if a state or a save slot is given,
+/// otherwise the options wait for the first round
+fn skip_title(options: Res<LaunchOptions>, seed_setting: Res<SeedSetting>, mut commands: Commands) {
+    let pending = PendingRunOptions {
+        money: options.money,
+        stats: options.preset.as_deref().and_then(PlayerStats::preset),
+    };
+
+    let slot = options
+        .slot
+        .as_deref()
+        .and_then(|name| match SaveSlot::load(name) {
+            Ok(slot) => {
+                info!("Loading save slot '{}'", name);
+                Some(slot)
+            }
+            Err(err) => {
+                warn!("Cannot load save slot '{}': {}", name, err);
+                None
+            }
+        });
+
+    let state = match (&options.state, &slot) {
+        (Some(GameState::Start), _) | (None, None) => {
+            commands.insert_resource(pending);
+            return;
+        }
+        (Some(state), _) => state.clone(),
+        // a saved run goes on in the shop it was saved in
+        (None, Some(_)) => GameState::Shoping,
+    };
+
+    let mode = slot
+        .as_ref()
+        .map(|slot| slot.mode)
+        .or(options.mode)
+        .unwrap_or_default();
+    start_run(&mut commands, mode, seed_setting.roll());
+
+    if let Some(slot) = slot {
+        let mut upgrades = get_upgrades();
+        for (upgrade, saved) in upgrades.iter_mut().zip(&slot.upgrades) {
+            upgrade.cur_up_count = saved.count;
+            upgrade.cost = saved.cost;
+        }
+        commands.insert_resource(Money(slot.money));
+        commands.insert_resource(slot.stats);
+        commands.insert_resource(UpgradeList(upgrades));
+    }
+
+    apply_pending(&mut commands, &pending);
+    commands.set_state(state);
+}
+
+pub(crate) fn apply_run_options(pending: Res<PendingRunOptions>, mut commands: Commands) {
+    apply_pending(&mut commands, &pending);
+    commands.remove_resource::<PendingRunOptions>();
+}
+
+fn apply_pending(commands: &mut Commands, pending: &PendingRunOptions) {
+    if let Some(money) = pending.money {
+        commands.insert_resource(Money(money));
+    }
+    if let Some(stats) = &pending.stats {
+        commands.insert_resource(stats.clone());
+    }
+}
+
+fn save_slot(
+    options: Res<LaunchOptions>,
+    mode: Res<GameMode>,
+    money: Res<Money>,
+    stats: Res<PlayerStats>,
+    upgrades: Res<UpgradeList>,
+) {
+    let Some(name) = &options.slot else {
+        return;
+    };
+
+    let slot = SaveSlot {
+        mode: *mode,
+        money: money.0,
+        stats: stats.clone(),
+        upgrades: upgrades
+            .0
+            .iter()
+            .map(|upgrade| SavedUpgrade {
+                count: upgrade.cur_up_count,
+                cost: upgrade.cost,
+            })
+            .collect(),
+    };
+    if let Err(err) = slot.save(name) {
+        warn!("Cannot save slot '{}': {}", name, err);
+    }
+}
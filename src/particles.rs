@@ -0,0 +1,244 @@
+use bevy::prelude::*;
+use rand::RngExt;
+
+/// Seconds until the wind blows a new gust
+const GUST_TIME: (f32, f32) = (3., 8.);
+/// Strongest gust, in pixels per second
+const MAX_GUST: f32 = 60.;
+/// Share of the way to the gust the wind goes per second
+const WIND_CHANGE: f32 = 0.5;
+
+/// Simple sprite particles of a [`ParticleEmitter`], pushed around by the [`Wind`]
+pub struct ParticlesPlugin;
+
+impl Plugin for ParticlesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Wind>()
+            .add_systems(Update, (blow_wind, emit_particles, move_particles).chain());
+    }
+}
+
+/// Wind over the whole screen, it turns to a new gust every few seconds
+#[derive(Debug, Resource, Default)]
+pub struct Wind {
+    pub velocity: Vec2,
+    gust: Vec2,
+    next_gust: f32,
+}
+
+/// Spawns particles in its area, they are its children and go away with it.
+/// Dead particles are hidden and reused for the next ones.
+#[derive(Debug, Component, Clone)]
+#[require(Transform, Visibility, EmitterState)]
+pub struct ParticleEmitter {
+    /// Particles per second
+    pub rate: f32,
+    /// Most particles alive at once
+    pub max_particles: usize,
+    /// Where particles start, relative to the emitter
+    pub area: Rect,
+    pub velocity: Vec2,
+    /// Random part of the velocity, up to this in every direction
+    pub velocity_spread: Vec2,
+    /// Seconds a particle lives if it does not settle before
+    pub lifetime: f32,
+    /// Smallest and biggest size in pixels
+    pub size: (f32, f32),
+    pub color: Color,
+    /// Share of the [`Wind`] that moves the particles
+    pub wind: f32,
+    /// Pixels the particles sway to the sides
+    pub sway: f32,
+    /// Height the particles settle at, up to `ground_spread` above it, relative to the emitter
+    pub ground: Option<f32>,
+    pub ground_spread: f32,
+    /// Seconds settled particles stay before they melt away, more than 0 if there is a ground
+    pub settle_time: f32,
+    /// The area starts full of particles instead of empty
+    pub prewarm: bool,
+}
+
+impl Default for ParticleEmitter {
+    fn default() -> Self {
+        Self {
+            rate: 10.,
+            max_particles: 100,
+            area: Rect::new(0., 0., 0., 0.),
+            velocity: Vec2::ZERO,
+            velocity_spread: Vec2::ZERO,
+            lifetime: 1.,
+            size: (2., 2.),
+            color: Color::WHITE,
+            wind: 0.,
+            sway: 0.,
+            ground: None,
+            ground_spread: 0.,
+            settle_time: 0.,
+            prewarm: false,
+        }
+    }
+}
+
+/// What a [`ParticleEmitter`] keeps from frame to frame
+#[derive(Debug, Component, Default)]
+struct EmitterState {
+    /// Particles to spawn, the fraction is carried to the next frame
+    pending: f32,
+    spawned: usize,
+    free: Vec<Entity>,
+    /// The first particles are out
+    started: bool,
+}
+
+#[derive(Debug, Component)]
+pub struct Particle {
+    velocity: Vec2,
+    age: f32,
+    /// Seconds it lies on the ground
+    settled: Option<f32>,
+    ground: Option<f32>,
+    /// Offset of the sway, so the particles do not sway in step
+    phase: f32,
+    /// Hidden until the emitter needs it again
+    dead: bool,
+}
+
+fn blow_wind(mut wind: ResMut<Wind>, time: Res<Time>) {
+    let mut rng = rand::rng();
+    wind.next_gust -= time.delta_secs();
+    if wind.next_gust <= 0. {
+        wind.next_gust = rng.random_range(GUST_TIME.0..GUST_TIME.1);
+        wind.gust = Vec2::new(rng.random_range(-MAX_GUST..MAX_GUST), 0.);
+    }
+
+    let change = (WIND_CHANGE * time.delta_secs()).min(1.);
+    wind.velocity = wind.velocity.lerp(wind.gust, change);
+}
+
+/// A new particle of the emitter, `age` seconds into its life
+fn new_particle(emitter: &ParticleEmitter, age: f32) -> (Particle, Sprite, Transform) {
+    let mut rng = rand::rng();
+    let spread = emitter.velocity_spread;
+    let velocity = emitter.velocity
+        + Vec2::new(
+            rng.random_range(-spread.x..=spread.x),
+            rng.random_range(-spread.y..=spread.y),
+        );
+    let area = emitter.area;
+    let start = Vec2::new(
+        rng.random_range(area.min.x..=area.max.x),
+        rng.random_range(area.min.y..=area.max.y),
+    );
+    let size = rng.random_range(emitter.size.0..=emitter.size.1);
+    let ground = emitter
+        .ground
+        .map(|ground| ground + rng.random_range(0. ..=emitter.ground_spread));
+
+    (
+        Particle {
+            velocity,
+            age,
+            settled: None,
+            ground,
+            phase: rng.random_range(0. ..std::f32::consts::TAU),
+            dead: false,
+        },
+        Sprite {
+            color: emitter.color,
+            custom_size: Some(Vec2::splat(size)),
+            ..Default::default()
+        },
+        Transform::from_translation((start + velocity * age).extend(0.)),
+    )
+}
+
+fn emit_particles(
+    mut commands: Commands,
+    mut emitters: Query<(Entity, &ParticleEmitter, &mut EmitterState)>,
+    time: Res<Time>,
+) {
+    for (entity, emitter, mut state) in &mut emitters {
+        let mut ages = Vec::new();
+        if emitter.prewarm && !state.started {
+            let count = (emitter.rate * emitter.lifetime) as usize;
+            let mut rng = rand::rng();
+            ages.extend((0..count).map(|_| rng.random_range(0. ..emitter.lifetime)));
+        }
+
+        state.started = true;
+
+        state.pending += emitter.rate * time.delta_secs();
+        let count = state.pending as usize;
+        state.pending -= count as f32;
+        ages.extend(std::iter::repeat_n(0., count));
+
+        for age in ages {
+            let particle = new_particle(emitter, age);
+            if let Some(free) = state.free.pop() {
+                commands
+                    .entity(free)
+                    .insert((particle, Visibility::Inherited));
+            } else if state.spawned < emitter.max_particles {
+                state.spawned += 1;
+                commands.spawn((particle, ChildOf(entity)));
+            }
+        }
+    }
+}
+
+fn move_particles(
+    mut particles: Query<(
+        Entity,
+        &mut Particle,
+        &mut Transform,
+        &mut Sprite,
+        &mut Visibility,
+        &ChildOf,
+    )>,
+    mut emitters: Query<(&ParticleEmitter, &mut EmitterState)>,
+    wind: Res<Wind>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_secs();
+    for (entity, mut particle, mut transform, mut sprite, mut visibility, child_of) in
+        &mut particles
+    {
+        let Ok((emitter, mut state)) = emitters.get_mut(child_of.parent()) else {
+            continue;
+        };
+        if particle.dead {
+            continue;
+        }
+        particle.age += delta;
+
+        let gone = match &mut particle.settled {
+            Some(settled) => {
+                *settled += delta;
+                let left = 1. - *settled / emitter.settle_time;
+                sprite.color = emitter
+                    .color
+                    .with_alpha(emitter.color.alpha() * left.max(0.));
+                left <= 0.
+            }
+            None => {
+                let sway = (particle.age * 2. + particle.phase).cos() * emitter.sway;
+                let velocity = particle.velocity + wind.velocity * emitter.wind + Vec2::X * sway;
+                transform.translation += (velocity * delta).extend(0.);
+
+                if let Some(ground) = particle.ground
+                    && transform.translation.y <= ground
+                {
+                    transform.translation.y = ground;
+                    particle.settled = Some(0.);
+                }
+                particle.settled.is_none() && particle.age > emitter.lifetime
+            }
+        };
+
+        if gone {
+            particle.dead = true;
+            *visibility = Visibility::Hidden;
+            state.free.push(entity);
+        }
+    }
+}
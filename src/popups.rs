@@ -0,0 +1,247 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::{
+    GameState, cli_flag,
+    events::{NutDamaged, NutDestroyed},
+};
+
+/// Seconds a popup rises before it is gone
+const POPUP_TIME: f32 = 1.;
+/// Pixels per second a popup rises
+const RISE_SPEED: f32 = 40.;
+/// A new popup this close to a young one adds to it instead
+const MERGE_DISTANCE: f32 = 48.;
+/// Age until a popup still takes more
+const MERGE_TIME: f32 = 0.3;
+/// More popups than that at once add to the nearest one
+const MAX_POPUPS: usize = 16;
+/// Seconds of damage a damage number sums up
+const DAMAGE_TICK: f32 = 0.25;
+/// Over the effects
+const POPUP_Z: f32 = 3.;
+
+/// Rising numbers in the forest, `+N` in the rarity color for every broken nut
+/// and with `--damage-numbers` the damage the laser does to a nut
+pub struct PopupPlugin;
+
+impl Plugin for PopupPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(PopupSettings {
+            damage_numbers: cli_flag("--damage-numbers"),
+        })
+        .init_resource::<DamageTally>()
+        .add_systems(OnExit(GameState::Playing), clear_damage_tally)
+        .add_systems(
+            Update,
+            (
+                spawn_money_popups,
+                tally_damage.run_if(|settings: Res<PopupSettings>| settings.damage_numbers),
+                rise_popups,
+            )
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+#[derive(Debug, Resource, Clone)]
+pub struct PopupSettings {
+    /// Ticking numbers while the laser burns a nut
+    pub damage_numbers: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PopupKind {
+    Money,
+    Damage,
+}
+
+#[derive(Debug, Component)]
+pub struct Popup {
+    pub kind: PopupKind,
+    /// Sum of everything the popup took
+    pub value: i32,
+    age: f32,
+    color: Color,
+}
+
+impl Popup {
+    fn text(&self) -> String {
+        match self.kind {
+            PopupKind::Money => format!("+{}", self.value),
+            PopupKind::Damage => self.value.to_string(),
+        }
+    }
+
+    fn font_size(&self) -> f32 {
+        match self.kind {
+            PopupKind::Money => 20.,
+            PopupKind::Damage => 12.,
+        }
+    }
+}
+
+/// Damage of every burning nut since its last number
+#[derive(Debug, Resource, Default)]
+struct DamageTally(HashMap<Entity, TalliedDamage>);
+
+#[derive(Debug)]
+struct TalliedDamage {
+    damage: f32,
+    time: f32,
+    pos: Vec2,
+}
+
+/// Popups of the frame not spawned yet, so they merge with each other too
+type PopupBatch = Vec<(Popup, Vec2)>;
+
+type PopupQuery<'w, 's> =
+    Query<'w, 's, (&'static mut Popup, &'static mut Text2d, &'static Transform)>;
+
+/// Adds to a young popup of the kind close by, or to the nearest one if there are
+/// too many, otherwise a new one goes in the batch
+fn add_popup(
+    popups: &mut PopupQuery,
+    batch: &mut PopupBatch,
+    kind: PopupKind,
+    value: i32,
+    pos: Vec2,
+    color: Color,
+) {
+    let crowded = popups.iter().count() + batch.len() >= MAX_POPUPS;
+    let takes = |popup: &Popup, at: Vec2| {
+        popup.kind == kind
+            && (crowded || popup.age < MERGE_TIME && at.distance(pos) < MERGE_DISTANCE)
+    };
+
+    let nearest = popups
+        .iter_mut()
+        .filter(|(popup, _, transform)| takes(popup, transform.translation.truncate()))
+        .min_by(|(_, _, a), (_, _, b)| {
+            let a = a.translation.truncate().distance_squared(pos);
+            let b = b.translation.truncate().distance_squared(pos);
+            a.total_cmp(&b)
+        });
+    if let Some((mut popup, mut text, _)) = nearest {
+        popup.value += value;
+        // it stays a bit longer with every addition
+        popup.age = popup.age.min(MERGE_TIME);
+        text.0 = popup.text();
+        return;
+    }
+
+    let batched = batch
+        .iter_mut()
+        .filter(|(popup, at)| takes(popup, *at))
+        .min_by(|(_, a), (_, b)| a.distance_squared(pos).total_cmp(&b.distance_squared(pos)));
+    if let Some((popup, _)) = batched {
+        popup.value += value;
+        return;
+    }
+
+    let popup = Popup {
+        kind,
+        value,
+        age: 0.,
+        color,
+    };
+    batch.push((popup, pos));
+}
+
+fn spawn_popups(commands: &mut Commands, batch: PopupBatch) {
+    for (popup, pos) in batch {
+        commands.spawn((
+            Text2d::new(popup.text()),
+            TextFont::from_font_size(popup.font_size()),
+            TextColor(popup.color),
+            TextLayout::new(Justify::Center, LineBreak::NoWrap),
+            Transform::from_translation(pos.extend(POPUP_Z)),
+            popup,
+            DespawnOnExit(GameState::Playing),
+        ));
+    }
+}
+
+fn spawn_money_popups(
+    mut commands: Commands,
+    mut destroyed: MessageReader<NutDestroyed>,
+    mut popups: PopupQuery,
+) {
+    let mut batch = PopupBatch::new();
+    for nut in destroyed.read() {
+        add_popup(
+            &mut popups,
+            &mut batch,
+            PopupKind::Money,
+            nut.value,
+            nut.pos,
+            nut.kind.color(),
+        );
+    }
+    spawn_popups(&mut commands, batch);
+}
+
+/// Sums the damage of every nut and shows it every [`DAMAGE_TICK`]
+fn tally_damage(
+    mut commands: Commands,
+    mut tally: ResMut<DamageTally>,
+    mut damaged: MessageReader<NutDamaged>,
+    mut popups: PopupQuery,
+    time: Res<Time>,
+) {
+    for nut in damaged.read() {
+        let entry = tally.0.entry(nut.entity).or_insert(TalliedDamage {
+            damage: 0.,
+            time: 0.,
+            pos: nut.pos,
+        });
+        entry.damage += nut.damage;
+        entry.pos = nut.pos;
+    }
+
+    for entry in tally.0.values_mut() {
+        entry.time += time.delta_secs();
+    }
+
+    // a broken nut or one out of the laser still shows its last damage
+    let mut batch = PopupBatch::new();
+    for (_, entry) in tally.0.extract_if(|_, entry| entry.time >= DAMAGE_TICK) {
+        let damage = entry.damage.round() as i32;
+        if damage > 0 {
+            add_popup(
+                &mut popups,
+                &mut batch,
+                PopupKind::Damage,
+                damage,
+                entry.pos + Vec2::new(0., 20.),
+                Color::srgb(1., 0.9, 0.9),
+            );
+        }
+    }
+    spawn_popups(&mut commands, batch);
+}
+
+fn rise_popups(
+    mut commands: Commands,
+    mut popups: Query<(Entity, &mut Popup, &mut Transform, &mut TextColor)>,
+    time: Res<Time>,
+) {
+    for (entity, mut popup, mut transform, mut color) in &mut popups {
+        popup.age += time.delta_secs();
+        if popup.age >= POPUP_TIME {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        transform.translation.y += RISE_SPEED * time.delta_secs();
+        // fades over the second half
+        let alpha = ((POPUP_TIME - popup.age) / (POPUP_TIME / 2.)).min(1.);
+        color.0 = popup.color.with_alpha(alpha);
+    }
+}
+
+fn clear_damage_tally(mut tally: ResMut<DamageTally>) {
+    tally.0.clear();
+}
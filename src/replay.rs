@@ -0,0 +1,199 @@
+use std::fs;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    GameState, Money, PlayerStats, cli_arg,
+    forest::{CubeInput, ForestSystems, LaserPoints},
+    game_mode::{GameMode, RunClock},
+    options::apply_run_options,
+    rng::GameRng,
+};
+
+/// Records a round with `--record <file>` or plays one back with `--replay <file>`
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        if let Some(path) = cli_arg("--replay") {
+            match Replay::load(&path) {
+                Ok(replay) => {
+                    info!("Playing replay '{}'", path);
+                    app.insert_resource(ReplayPlayer { replay, tick: 0 });
+                }
+                Err(err) => warn!("Cannot load replay '{}': {}", path, err),
+            }
+        } else if let Some(path) = cli_arg("--record") {
+            info!("Recording the last played round to '{}'", path);
+            app.insert_resource(ReplayRecorder { path, replay: None });
+        }
+
+        let playing = app.world().contains_resource::<ReplayPlayer>();
+        let recording = app.world().contains_resource::<ReplayRecorder>();
+        if !playing && !recording {
+            return;
+        }
+
+        app.add_systems(
+            Update,
+            start_playback
+                .run_if(in_state(GameState::Start))
+                .run_if(resource_exists::<ReplayPlayer>),
+        )
+        .add_systems(
+            OnEnter(GameState::Playing),
+            start_recording
+                .run_if(resource_exists::<ReplayRecorder>)
+                .after(apply_run_options),
+        )
+        .add_systems(
+            FixedUpdate,
+            (
+                play_input.run_if(resource_exists::<ReplayPlayer>),
+                record_input.run_if(resource_exists::<ReplayRecorder>),
+            )
+                .chain()
+                .in_set(ForestSystems::Input),
+        )
+        .add_systems(
+            OnExit(GameState::Playing),
+            (
+                finish_recording.run_if(resource_exists::<ReplayRecorder>),
+                finish_playback.run_if(resource_exists::<ReplayPlayer>),
+            ),
+        );
+    }
+}
+
+/// Everything needed to simulate one round again
+#[derive(Debug, Serialize, Deserialize)]
+struct Replay {
+    mode: GameMode,
+    seed: u64,
+    money: i32,
+    stats: PlayerStats,
+    /// Seconds of the run before the round, nuts of the Endless mode grow with it
+    #[serde(default)]
+    clock: f32,
+    /// Cube position fed to the forest, one entry per fixed tick
+    ticks: Vec<Option<Vec2>>,
+    result: ReplayResult,
+}
+
+/// State at the end of the round, to check a playback against
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+struct ReplayResult {
+    money: i32,
+    laser: Vec<(Vec2, Vec2)>,
+}
+
+impl Replay {
+    fn load(path: &str) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&content).map_err(|e| e.to_string())
+    }
+
+    fn save(&self, path: &str) -> Result<(), String> {
+        let content = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        fs::write(path, content).map_err(|e| e.to_string())
+    }
+}
+
+#[derive(Resource)]
+struct ReplayRecorder {
+    path: String,
+    replay: Option<Replay>,
+}
+
+#[derive(Resource)]
+struct ReplayPlayer {
+    replay: Replay,
+    tick: usize,
+}
+
+fn start_playback(player: Res<ReplayPlayer>, mut commands: Commands) {
+    let replay = &player.replay;
+    commands.insert_resource(replay.mode);
+    commands.insert_resource(replay.stats.clone());
+    commands.insert_resource(Money(replay.money));
+    commands.insert_resource(RunClock(replay.clock));
+    commands.insert_resource(GameRng::with_round_seed(replay.seed));
+    commands.set_state(GameState::Playing);
+}
+
+fn play_input(mut player: ResMut<ReplayPlayer>, mut input: ResMut<CubeInput>) {
+    let tick = player.tick;
+    input.0 = player.replay.ticks.get(tick).copied().flatten();
+    player.tick += 1;
+}
+
+fn start_recording(
+    mut recorder: ResMut<ReplayRecorder>,
+    mode: Res<GameMode>,
+    money: Res<Money>,
+    stats: Res<PlayerStats>,
+    clock: Res<RunClock>,
+) {
+    recorder.replay = Some(Replay {
+        mode: *mode,
+        seed: 0,
+        money: money.0,
+        stats: stats.clone(),
+        clock: clock.0,
+        ticks: vec![],
+        result: ReplayResult::default(),
+    });
+}
+
+fn record_input(mut recorder: ResMut<ReplayRecorder>, input: Res<CubeInput>) {
+    if let Some(replay) = &mut recorder.replay {
+        replay.ticks.push(input.0);
+    }
+}
+
+fn finish_recording(
+    mut recorder: ResMut<ReplayRecorder>,
+    rng: Res<GameRng>,
+    money: Res<Money>,
+    laser: Res<LaserPoints>,
+) {
+    let path = recorder.path.clone();
+    let Some(mut replay) = recorder.replay.take() else {
+        return;
+    };
+
+    // the round seed is set while the forest is set up, it stays the same for the whole round
+    replay.seed = rng.seed;
+    replay.result = ReplayResult {
+        money: money.0,
+        laser: laser.list.clone(),
+    };
+
+    match replay.save(&path) {
+        Ok(()) => info!("Saved replay of {} ticks to '{}'", replay.ticks.len(), path),
+        Err(err) => warn!("Cannot save replay '{}': {}", path, err),
+    }
+}
+
+fn finish_playback(
+    player: Res<ReplayPlayer>,
+    money: Res<Money>,
+    laser: Res<LaserPoints>,
+    mut exit: MessageWriter<AppExit>,
+) {
+    let result = ReplayResult {
+        money: money.0,
+        laser: laser.list.clone(),
+    };
+
+    if result == player.replay.result {
+        info!("Replay matches after {} ticks", player.tick);
+    } else {
+        warn!(
+            "Replay diverged after {} ticks\nrecorded: {:?}\nplayed:   {:?}",
+            player.tick, player.replay.result, result
+        );
+    }
+    exit.write(AppExit::Success);
+}
@@ -0,0 +1,33 @@
+//! Text output of the command line tools
+
+/// One line of a table, a cell per column
+pub type Row = Vec<String>;
+
+/// Lines of the table with right aligned columns, the header first
+pub fn table(header: &Row, rows: &[Row]) -> Vec<String> {
+    let widths: Vec<usize> = (0..header.len())
+        .map(|i| {
+            rows.iter()
+                .map(|r| r[i].len())
+                .chain([header[i].len()])
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+
+    let line = |row: &Row| {
+        row.iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:>width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join(" | ")
+    };
+
+    [header].into_iter().chain(rows).map(line).collect()
+}
+
+pub fn print_table(header: &Row, rows: &[Row]) {
+    for line in table(header, rows) {
+        println!("{}", line);
+    }
+}
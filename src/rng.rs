@@ -0,0 +1,144 @@
+use bevy::{
+    input::keyboard::{Key, KeyboardInput},
+    prelude::*,
+};
+use chrono::Datelike;
+use rand::{RngExt, SeedableRng, rngs::StdRng};
+
+use crate::{GameState, cli_arg};
+
+pub struct RngPlugin;
+
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SeedSetting(seed_from_args()))
+            .insert_resource(GameRng::new(0))
+            .add_systems(OnEnter(GameState::Start), setup_seed_label)
+            .add_systems(
+                Update,
+                (edit_seed.in_set(SeedInputSystems), update_seed_label)
+                    .chain()
+                    .run_if(in_state(GameState::Start)),
+            );
+    }
+}
+
+/// Reads the typed seed on the title screen, paused while something else takes the keyboard
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SeedInputSystems;
+
+/// Seed the next run starts with, a random one is rolled if empty
+#[derive(Resource, Debug)]
+pub struct SeedSetting(pub Option<u64>);
+
+/// Source of all gameplay randomness, reseeded at the start of every run
+#[derive(Resource, Deref, DerefMut)]
+pub struct GameRng {
+    /// Seed of the running round
+    pub seed: u64,
+    #[deref]
+    rng: StdRng,
+    next_round: Option<u64>,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            next_round: None,
+        }
+    }
+
+    /// The next round starts with exactly this seed, used to play back a recorded round
+    pub fn with_round_seed(seed: u64) -> Self {
+        Self {
+            next_round: Some(seed),
+            ..Self::new(seed)
+        }
+    }
+
+    /// Switch to a fresh stream for the round, so every round can be reproduced on its own
+    pub fn start_round(&mut self) {
+        let seed = self.next_round.take().unwrap_or_else(|| self.rng.random());
+        self.seed = seed;
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+}
+
+impl SeedSetting {
+    /// The configured seed or a fresh random one
+    pub fn roll(&self) -> u64 {
+        self.0.unwrap_or_else(|| rand::rng().random())
+    }
+}
+
+/// Seed shared by everyone who plays on the same local date, e.g. 20261018
+pub fn daily_seed() -> u64 {
+    let today = chrono::Local::now().date_naive();
+    today.year() as u64 * 10000 + today.month() as u64 * 100 + today.day() as u64
+}
+
+/// Reads `--seed <number>` from the command line
+pub fn seed_from_args() -> Option<u64> {
+    let seed = cli_arg("--seed")?;
+    match seed.parse() {
+        Ok(seed) => Some(seed),
+        Err(_) => {
+            warn!("Invalid seed '{}'", seed);
+            None
+        }
+    }
+}
+
+#[derive(Debug, Component)]
+struct SeedLabel;
+
+fn setup_seed_label(mut commands: Commands) {
+    commands.spawn((
+        SeedLabel,
+        Text2d::new(""),
+        TextFont::from_font_size(16.),
+        TextLayout::new(Justify::Center, LineBreak::NoWrap),
+        Transform::from_xyz(0., -120., 0.),
+        DespawnOnExit(GameState::Start),
+    ));
+}
+
+/// Type digits on the title screen to set the seed, backspace to delete
+fn edit_seed(mut reader: MessageReader<KeyboardInput>, mut setting: ResMut<SeedSetting>) {
+    for input in reader.read() {
+        if !input.state.is_pressed() {
+            continue;
+        }
+
+        match &input.logical_key {
+            Key::Character(c) if c.chars().all(|c| c.is_ascii_digit()) => {
+                let digits = format!(
+                    "{}{}",
+                    setting.0.map(|s| s.to_string()).unwrap_or_default(),
+                    c
+                );
+                // ignore digits that would overflow the seed
+                if let Ok(seed) = digits.parse() {
+                    setting.0 = Some(seed);
+                }
+            }
+            Key::Backspace => {
+                setting.0 = setting.0.map(|s| s / 10).filter(|s| *s > 0);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn update_seed_label(setting: Res<SeedSetting>, mut label: Single<&mut Text2d, With<SeedLabel>>) {
+    if !setting.is_changed() && !label.0.is_empty() {
+        return;
+    }
+
+    label.0 = match setting.0 {
+        Some(seed) => format!("Seed: {}", seed),
+        None => "Seed: random (type to set)".into(),
+    };
+}
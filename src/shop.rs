@@ -1,36 +1,73 @@
 use bevy::prelude::*;
 
-use crate::{GameState, Money, PlayerStats, UpgradeList};
+use crate::{
+    GameState, Money, PlayerStats, UpgradeList,
+    audio::{AudioManager, Sound},
+    events::UpgradePurchased,
+};
 
 const UPGRADE_BUTTON_SIZE: Vec2 = Vec2::new(150., 80.);
 const UPGRADE_FIELD_MARGIN: Vec2 = Vec2::new(100., 50.);
 
+/// Buying upgrades, without any UI
 pub struct ShopPlugin;
 
 impl Plugin for ShopPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<BuyUpgradeMessage>()
+            .add_message::<ChangedUpgradeState>()
+            .add_message::<UpgradePurchased>()
+            .add_systems(
+                Update,
+                buy_upgrades
+                    .in_set(PurchaseSystems)
+                    .run_if(in_state(GameState::Shoping)),
+            );
+    }
+}
+
+pub struct ShopUiPlugin;
+
+impl Plugin for ShopUiPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(GameState::Shoping), setup_shop)
             .add_message::<ButtonClickedMessage>()
             .add_message::<MoneyLabelUpdatedMessage>()
             .add_message::<SpawnUpgradeButtonMessage>()
-            .add_message::<ChangedUpgradeState>()
+            .add_systems(
+                Update,
+                (check_buttons, read_upgrade_button)
+                    .chain()
+                    .before(PurchaseSystems)
+                    .run_if(in_state(GameState::Shoping)),
+            )
             .add_systems(
                 Update,
                 (
-                    check_buttons,
-                    read_upgrade_button,
                     spawn_upgrade,
-                    update_money_label,
                     update_changed_upgrade_ui,
+                    update_money_after_purchase,
+                    update_money_label,
                     update_available_upgrade_money,
                     read_new_round_button,
                 )
                     .chain()
+                    .after(PurchaseSystems)
                     .run_if(in_state(GameState::Shoping)),
             );
     }
 }
 
+/// Applies the [`BuyUpgradeMessage`]s of the frame
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PurchaseSystems;
+
+/// Try to buy one level of the upgrade
+#[derive(Message, Debug)]
+pub struct BuyUpgradeMessage {
+    pub upgrade_id: usize,
+}
+
 #[derive(Message, Debug)]
 struct ButtonClickedMessage(Entity);
 
@@ -43,8 +80,8 @@ struct SpawnUpgradeButtonMessage {
 }
 
 #[derive(Message, Debug)]
-struct ChangedUpgradeState {
-    upgrade_id: usize,
+pub struct ChangedUpgradeState {
+    pub upgrade_id: usize,
 }
 
 #[derive(Component, Debug)]
@@ -211,13 +248,11 @@ fn spawn_upgrade(
 fn check_buttons(
     query: Query<(Entity, &Interaction), (Changed<Interaction>, With<Button>)>,
     mut clicked_writer: MessageWriter<ButtonClickedMessage>,
-    asset_server: Res<AssetServer>,
-    mut commands: Commands,
+    mut audio: ResMut<AudioManager>,
 ) {
     for (entity, interaction) in &query {
         if *interaction == Interaction::Pressed {
-            let click_sound = asset_server.load("embedded://button.wav");
-            commands.spawn((AudioPlayer::new(click_sound), PlaybackSettings::DESPAWN));
+            audio.play(Sound::Click);
             clicked_writer.write(ButtonClickedMessage(entity));
         }
     }
@@ -230,7 +265,6 @@ fn read_new_round_button(
 ) {
     for msg in reader.read() {
         if let Ok(_) = query.get(msg.0) {
-            println!("NExt Round");
             commands.set_state(GameState::Playing);
         }
     }
@@ -238,31 +272,61 @@ fn read_new_round_button(
 
 fn read_upgrade_button(
     mut reader: MessageReader<ButtonClickedMessage>,
-    mut query: Query<&UpgradeComponent, With<UpgradeNode>>,
+    query: Query<&UpgradeComponent, With<UpgradeNode>>,
+    mut buy_writer: MessageWriter<BuyUpgradeMessage>,
+) {
+    for msg in reader.read() {
+        if let Ok(upgrade_comp) = query.get(msg.0) {
+            buy_writer.write(BuyUpgradeMessage {
+                upgrade_id: upgrade_comp.0,
+            });
+        }
+    }
+}
+
+fn buy_upgrades(
+    mut reader: MessageReader<BuyUpgradeMessage>,
     mut player_stats: ResMut<PlayerStats>,
     mut money: ResMut<Money>,
-    mut money_writer: MessageWriter<MoneyLabelUpdatedMessage>,
     mut upgrade_list: ResMut<UpgradeList>,
     mut changed_upgrade_writer: MessageWriter<ChangedUpgradeState>,
+    mut purchased_writer: MessageWriter<UpgradePurchased>,
 ) {
     for msg in reader.read() {
-        if let Ok(upgrade_comp) = query.get_mut(msg.0) {
-            println!("Action! id:-");
-            if let Some(upgrade) = upgrade_list.0.iter_mut().find(|u| u.id == upgrade_comp.0) {
-                let stats = &mut *player_stats;
-                let mon = &mut *money;
-                (upgrade.increase_value)(upgrade, stats, mon);
-                money_writer.write(MoneyLabelUpdatedMessage(mon.0));
-                changed_upgrade_writer.write(ChangedUpgradeState {
-                    upgrade_id: upgrade.id,
+        if let Some(upgrade) = upgrade_list.0.iter_mut().find(|u| u.id == msg.upgrade_id) {
+            let stats = &mut *player_stats;
+            let mon = &mut *money;
+            let count = upgrade.cur_up_count;
+            let cost = upgrade.cost;
+            (upgrade.increase_value)(upgrade, stats, mon);
+            if upgrade.cur_up_count > count {
+                purchased_writer.write(UpgradePurchased {
+                    id: upgrade.id,
+                    title: upgrade.title.clone(),
+                    value_hint: upgrade.value_hint.clone(),
+                    cost,
+                    level: upgrade.cur_up_count,
                 });
-            } else {
-                println!("Cannot upgrade id: {}", upgrade_comp.0);
             }
+            changed_upgrade_writer.write(ChangedUpgradeState {
+                upgrade_id: upgrade.id,
+            });
+        } else {
+            warn!("Cannot upgrade id: {}", msg.upgrade_id);
         }
     }
 }
 
+fn update_money_after_purchase(
+    mut changed_upgrade_reader: MessageReader<ChangedUpgradeState>,
+    money: Res<Money>,
+    mut money_writer: MessageWriter<MoneyLabelUpdatedMessage>,
+) {
+    for _ in changed_upgrade_reader.read() {
+        money_writer.write(MoneyLabelUpdatedMessage(money.0));
+    }
+}
+
 fn update_changed_upgrade_ui(
     mut changed_upgrade_reader: MessageReader<ChangedUpgradeState>,
     mut query: Query<(
@@ -0,0 +1,124 @@
+use bevy::prelude::*;
+
+use crate::{GameState, cli_arg, particles::ParticleEmitter};
+
+/// Top of the screen, where the flakes start
+const SKY: f32 = 380.;
+/// Wide enough that the wind does not blow the screen empty
+const HALF_WIDTH: f32 = 800.;
+
+/// Snow falling over the forest in three layers, the far ones small and slow
+pub struct SnowPlugin;
+
+impl Plugin for SnowPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SnowSettings::from_args())
+            .add_systems(OnEnter(GameState::Playing), spawn_snow);
+    }
+}
+
+/// Amount of snow from `--snow <density>`, 1 by default and 0 for none
+#[derive(Debug, Resource, Clone)]
+pub struct SnowSettings {
+    pub density: f32,
+}
+
+impl SnowSettings {
+    fn from_args() -> Self {
+        let density = cli_arg("--snow").and_then(|density| density.parse::<f32>().ok());
+        Self {
+            density: density.unwrap_or(1.).max(0.),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum SnowLayer {
+    /// Behind everything, settles further up the ground
+    Far,
+    /// Behind the cubes, settles at the bottom
+    Middle,
+    /// Big flakes in front of everything
+    Near,
+}
+
+impl SnowLayer {
+    const ALL: [SnowLayer; 3] = [SnowLayer::Far, SnowLayer::Middle, SnowLayer::Near];
+
+    fn emitter(&self, density: f32) -> ParticleEmitter {
+        let area = Rect::new(-HALF_WIDTH, SKY, HALF_WIDTH, SKY + 20.);
+        let emitter = match self {
+            SnowLayer::Far => ParticleEmitter {
+                rate: 25.,
+                velocity: Vec2::new(0., -25.),
+                velocity_spread: Vec2::new(4., 5.),
+                lifetime: 30.,
+                size: (1., 2.),
+                color: Color::srgba(1., 1., 1., 0.5),
+                wind: 0.3,
+                sway: 4.,
+                ground: Some(-290.),
+                ground_spread: 30.,
+                settle_time: 4.,
+                ..Default::default()
+            },
+            SnowLayer::Middle => ParticleEmitter {
+                rate: 15.,
+                velocity: Vec2::new(0., -45.),
+                velocity_spread: Vec2::new(6., 8.),
+                lifetime: 20.,
+                size: (2., 3.),
+                color: Color::srgba(1., 1., 1., 0.8),
+                wind: 0.6,
+                sway: 8.,
+                ground: Some(-355.),
+                ground_spread: 12.,
+                settle_time: 6.,
+                ..Default::default()
+            },
+            SnowLayer::Near => ParticleEmitter {
+                rate: 5.,
+                velocity: Vec2::new(0., -90.),
+                velocity_spread: Vec2::new(10., 15.),
+                lifetime: 10.,
+                size: (4., 6.),
+                color: Color::srgba(1., 1., 1., 0.9),
+                wind: 1.,
+                sway: 14.,
+                ..Default::default()
+            },
+        };
+
+        let rate = emitter.rate * density;
+        ParticleEmitter {
+            rate,
+            max_particles: (rate * (emitter.lifetime + emitter.settle_time)) as usize,
+            area,
+            prewarm: true,
+            ..emitter
+        }
+    }
+
+    fn z(&self) -> f32 {
+        match self {
+            // just over the background
+            SnowLayer::Far => -0.9,
+            SnowLayer::Middle => -0.5,
+            SnowLayer::Near => 4.,
+        }
+    }
+}
+
+fn spawn_snow(mut commands: Commands, settings: Res<SnowSettings>) {
+    if settings.density <= 0. {
+        return;
+    }
+
+    for layer in SnowLayer::ALL {
+        commands.spawn((
+            layer.emitter(settings.density),
+            Transform::from_xyz(0., 0., layer.z()),
+            DespawnOnExit(GameState::Playing),
+        ));
+    }
+}
@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    prelude::*,
+};
+use serde::Deserialize;
+
+use crate::aseprite::AsepriteLoader;
+
+/// Plays the clips of an [`AnimationSheet`] on sprites, sheets load from `*.sheet.json` files
+/// and straight from `.aseprite` files
+pub struct SpriteAnimationPlugin;
+
+impl Plugin for SpriteAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<AnimationSheet>()
+            .init_asset_loader::<AnimationSheetLoader>()
+            .init_asset_loader::<AsepriteLoader>()
+            .add_systems(PostUpdate, animate_sprites);
+    }
+}
+
+/// The grid of a sprite sheet and its named clips
+#[derive(Debug, Asset, TypePath)]
+pub struct AnimationSheet {
+    /// Image the sheet brings along, otherwise the sprite keeps its own
+    pub image: Option<Handle<Image>>,
+    pub layout: Handle<TextureAtlasLayout>,
+    pub clips: HashMap<String, SpriteClip>,
+}
+
+/// Frames of the sheet played one after the other
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpriteClip {
+    /// Indices into the grid of the sheet
+    pub frames: Vec<usize>,
+    /// Frames per second, life clips leave it out
+    #[serde(default)]
+    pub fps: f32,
+    /// Seconds of every frame instead of the `fps`
+    #[serde(default)]
+    pub durations: Vec<f32>,
+    pub mode: ClipMode,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClipMode {
+    /// Starts over at the end
+    Loop,
+    /// Stays on the last frame
+    Once,
+    /// Not played over time, the frame follows [`SpriteAnimation::progress`]
+    Life,
+}
+
+impl SpriteClip {
+    /// Frame of the sheet `time` seconds into the clip or at `progress` of a life clip
+    pub fn frame(&self, time: f32, progress: f32) -> usize {
+        let count = self.frames.len().max(1);
+        let step = match self.mode {
+            ClipMode::Loop => self.steps(time) % count,
+            ClipMode::Once => self.steps(time).min(count - 1),
+            ClipMode::Life => (progress.clamp(0., 1.) * (count - 1) as f32) as usize,
+        };
+        self.frames.get(step).copied().unwrap_or(0)
+    }
+
+    /// Frames played after `time` seconds, counting on over the end of the clip
+    fn steps(&self, time: f32) -> usize {
+        let total: f32 = self.durations.iter().sum();
+        if self.durations.is_empty() || total <= 0. {
+            return (time * self.fps) as usize;
+        }
+
+        let rounds = (time / total) as usize;
+        let mut left = time - rounds as f32 * total;
+        let mut step = 0;
+        for duration in &self.durations {
+            if left < *duration {
+                break;
+            }
+            left -= duration;
+            step += 1;
+        }
+        rounds * self.durations.len() + step
+    }
+}
+
+/// Plays a clip of the sheet on the [`Sprite`] of the entity
+#[derive(Debug, Component)]
+pub struct SpriteAnimation {
+    pub sheet: Handle<AnimationSheet>,
+    /// 0 to 1, picks the frame of a life clip
+    pub progress: f32,
+    clip: String,
+    time: f32,
+}
+
+impl SpriteAnimation {
+    pub fn new(sheet: Handle<AnimationSheet>, clip: &str) -> Self {
+        Self {
+            sheet,
+            progress: 0.,
+            clip: clip.to_string(),
+            time: 0.,
+        }
+    }
+
+    pub fn clip(&self) -> &str {
+        &self.clip
+    }
+
+    /// Switches to the clip from its start, the clip already playing goes on
+    pub fn play(&mut self, clip: &str) {
+        if self.clip != clip {
+            self.clip = clip.to_string();
+            self.time = 0.;
+        }
+    }
+}
+
+/// A sheet as it is written in the file, the grid becomes the layout
+#[derive(Debug, Deserialize)]
+struct SheetFile {
+    frame_size: UVec2,
+    columns: u32,
+    rows: u32,
+    clips: HashMap<String, SpriteClip>,
+}
+
+#[derive(Debug, Default, TypePath)]
+struct AnimationSheetLoader;
+
+impl AssetLoader for AnimationSheetLoader {
+    type Asset = AnimationSheet;
+    type Settings = ();
+    type Error = BevyError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<AnimationSheet, BevyError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let file: SheetFile = serde_json::from_slice(&bytes)?;
+
+        let frames = (file.columns * file.rows) as usize;
+        for (name, clip) in &file.clips {
+            if clip.frames.is_empty() || clip.frames.iter().any(|&frame| frame >= frames) {
+                return Err(format!("clip {name} needs frames between 0 and {frames}").into());
+            }
+        }
+
+        let layout =
+            TextureAtlasLayout::from_grid(file.frame_size, file.columns, file.rows, None, None);
+        Ok(AnimationSheet {
+            image: None,
+            layout: load_context.add_labeled_asset("layout".to_string(), layout),
+            clips: file.clips,
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["sheet.json"]
+    }
+}
+
+fn animate_sprites(
+    mut sprites: Query<(&mut SpriteAnimation, &mut Sprite)>,
+    sheets: Res<Assets<AnimationSheet>>,
+    time: Res<Time>,
+) {
+    for (mut animation, mut sprite) in &mut sprites {
+        let Some(sheet) = sheets.get(&animation.sheet) else {
+            continue;
+        };
+        let Some(clip) = sheet.clips.get(&animation.clip) else {
+            continue;
+        };
+        animation.time += time.delta_secs();
+        let index = clip.frame(animation.time, animation.progress);
+
+        if let Some(image) = &sheet.image
+            && sprite.image != *image
+        {
+            sprite.image = image.clone();
+        }
+        match &mut sprite.texture_atlas {
+            Some(atlas) if atlas.layout == sheet.layout => atlas.index = index,
+            atlas => {
+                *atlas = Some(TextureAtlas {
+                    layout: sheet.layout.clone(),
+                    index,
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use std::io::Write;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    NutType, PlayerStats, cli_arg, cli_flag,
+    events::{CubeLossCause, CubeLost, NutDestroyed, RoundEnded, RoundStarted, UpgradePurchased},
+    game_mode::GameMode,
+};
+
+/// Folder of the logs if `--telemetry-dir` is not given
+pub const TELEMETRY_DIR: &str = "telemetry";
+
+/// Logs the events of the session to a JSONL file with `--telemetry [--telemetry-dir <folder>]`,
+/// one file per session, for `cargo run --bin telemetry` to sum up
+pub struct TelemetryPlugin;
+
+impl Plugin for TelemetryPlugin {
+    fn build(&self, app: &mut App) {
+        if !cli_flag("--telemetry") {
+            return;
+        }
+
+        let dir = cli_arg("--telemetry-dir").unwrap_or(TELEMETRY_DIR.into());
+        match TelemetryLog::create(&dir) {
+            Ok(log) => {
+                info!("Writing telemetry to '{}'", log.path);
+                app.insert_resource(log);
+            }
+            Err(err) => {
+                warn!("Cannot write telemetry to '{}': {}", dir, err);
+                return;
+            }
+        }
+
+        app.add_systems(Update, (log_rounds, log_round_events).chain());
+    }
+}
+
+/// One line of the log
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TelemetryRecord {
+    /// Seconds of game time since the session started
+    pub time: f32,
+    /// Rounds started in the session so far, 0 before the first one
+    pub round: u32,
+    #[serde(flatten)]
+    pub event: TelemetryEvent,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum TelemetryEvent {
+    SessionStart {
+        /// Local date and time, e.g. `2026-10-18T14:03:12`
+        date: String,
+    },
+    RoundStart {
+        mode: GameMode,
+        seed: u64,
+        stats: PlayerStats,
+    },
+    RoundEnd {
+        mode: GameMode,
+        score: i32,
+        #[serde(default)]
+        run_score: i32,
+        new_high_score: bool,
+    },
+    NutKill {
+        kind: NutType,
+        value: i32,
+        pos: Vec2,
+    },
+    Purchase {
+        /// Title and value hint, e.g. `Damage + 5`
+        upgrade: String,
+        cost: i32,
+        level: i32,
+    },
+    CubeDeath {
+        cause: CubeLossCause,
+        cubes_left: i32,
+    },
+}
+
+#[derive(Resource)]
+struct TelemetryLog {
+    path: String,
+    file: std::io::LineWriter<std::fs::File>,
+    round: u32,
+}
+
+impl TelemetryLog {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn create(dir: &str) -> Result<Self, String> {
+        let now = chrono::Local::now();
+        let path = format!("{}/{}.jsonl", dir, now.format("%Y-%m-%d_%H-%M-%S"));
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        let file = std::fs::File::create(&path).map_err(|e| e.to_string())?;
+
+        let mut log = Self {
+            path,
+            file: std::io::LineWriter::new(file),
+            round: 0,
+        };
+        log.write(
+            0.,
+            TelemetryEvent::SessionStart {
+                date: now.format("%Y-%m-%dT%H:%M:%S").to_string(),
+            },
+        );
+        Ok(log)
+    }
+
+    // the browser build has no files to log to
+    #[cfg(target_arch = "wasm32")]
+    fn create(_dir: &str) -> Result<Self, String> {
+        Err("Telemetry is not supported in the browser".into())
+    }
+
+    fn write(&mut self, time: f32, event: TelemetryEvent) {
+        let record = TelemetryRecord {
+            time,
+            round: self.round,
+            event,
+        };
+        let result = serde_json::to_string(&record)
+            .map_err(|e| e.to_string())
+            .and_then(|line| writeln!(self.file, "{}", line).map_err(|e| e.to_string()));
+        if let Err(err) = result {
+            warn!("Cannot write telemetry: {}", err);
+        }
+    }
+}
+
+fn log_rounds(
+    mut log: ResMut<TelemetryLog>,
+    time: Res<Time>,
+    stats: Res<PlayerStats>,
+    mut started: MessageReader<RoundStarted>,
+    mut ended: MessageReader<RoundEnded>,
+) {
+    let now = time.elapsed_secs();
+
+    for round in started.read() {
+        log.round += 1;
+        log.write(
+            now,
+            TelemetryEvent::RoundStart {
+                mode: round.mode,
+                seed: round.seed,
+                stats: stats.clone(),
+            },
+        );
+    }
+    for round in ended.read() {
+        log.write(
+            now,
+            TelemetryEvent::RoundEnd {
+                mode: round.mode,
+                score: round.score,
+                run_score: round.run_score,
+                new_high_score: round.new_high_score,
+            },
+        );
+    }
+}
+
+fn log_round_events(
+    mut log: ResMut<TelemetryLog>,
+    time: Res<Time>,
+    mut destroyed: MessageReader<NutDestroyed>,
+    mut lost: MessageReader<CubeLost>,
+    mut purchased: MessageReader<UpgradePurchased>,
+) {
+    let now = time.elapsed_secs();
+
+    for nut in destroyed.read() {
+        log.write(
+            now,
+            TelemetryEvent::NutKill {
+                kind: nut.kind,
+                value: nut.value,
+                pos: nut.pos,
+            },
+        );
+    }
+    for cube in lost.read() {
+        log.write(
+            now,
+            TelemetryEvent::CubeDeath {
+                cause: cube.cause,
+                cubes_left: cube.cubes_left,
+            },
+        );
+    }
+    for upgrade in purchased.read() {
+        log.write(
+            now,
+            TelemetryEvent::Purchase {
+                upgrade: format!("{} {}", upgrade.title, upgrade.value_hint),
+                cost: upgrade.cost,
+                level: upgrade.level,
+            },
+        );
+    }
+}
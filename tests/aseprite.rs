@@ -0,0 +1,237 @@
+mod harness;
+
+use std::{thread, time::Duration};
+
+use bevy::prelude::*;
+use cozy_winter::{
+    aseprite::{ALL_FRAMES, Aseprite},
+    sprite_animation::{AnimationSheet, ClipMode},
+};
+use harness::TestApp;
+
+const RED: [u8; 4] = [255, 0, 0, 255];
+const GREEN: [u8; 4] = [0, 255, 0, 255];
+const WHITE: [u8; 4] = [255, 255, 255, 255];
+
+fn string(name: &str) -> Vec<u8> {
+    let mut bytes = (name.len() as u16).to_le_bytes().to_vec();
+    bytes.extend(name.as_bytes());
+    bytes
+}
+
+fn chunk(kind: u16, body: Vec<u8>) -> Vec<u8> {
+    let mut bytes = (body.len() as u32 + 6).to_le_bytes().to_vec();
+    bytes.extend(kind.to_le_bytes());
+    bytes.extend(body);
+    bytes
+}
+
+fn layer(name: &str, visible: bool) -> Vec<u8> {
+    let mut body = vec![visible as u8, 0, 0, 0, 0, 0];
+    body.extend([0; 6]);
+    body.push(255);
+    body.extend([0; 3]);
+    body.extend(string(name));
+    chunk(0x2004, body)
+}
+
+/// A 1x1 cel of the color
+fn cel(layer: u16, color: [u8; 4]) -> Vec<u8> {
+    let mut body = layer.to_le_bytes().to_vec();
+    body.extend([0, 0, 0, 0, 255, 0, 0, 0, 0]);
+    body.extend([0; 5]);
+    body.extend([1, 0, 1, 0]);
+    body.extend(color);
+    chunk(0x2005, body)
+}
+
+fn linked_cel(layer: u16, frame: u16) -> Vec<u8> {
+    let mut body = layer.to_le_bytes().to_vec();
+    body.extend([0, 0, 0, 0, 255, 1, 0, 0, 0]);
+    body.extend([0; 5]);
+    body.extend(frame.to_le_bytes());
+    chunk(0x2005, body)
+}
+
+/// Tags of `(name, from, to, direction, repeat)`
+fn tags(tags: &[(&str, u16, u16, u8, u16)]) -> Vec<u8> {
+    let mut body = (tags.len() as u16).to_le_bytes().to_vec();
+    body.extend([0; 8]);
+    for (name, from, to, direction, repeat) in tags {
+        body.extend(from.to_le_bytes());
+        body.extend(to.to_le_bytes());
+        body.push(*direction);
+        body.extend(repeat.to_le_bytes());
+        body.extend([0; 10]);
+        body.extend(string(name));
+    }
+    chunk(0x2018, body)
+}
+
+/// User data with the text, it belongs to the chunk or the tag before it
+fn user_data(text: &str) -> Vec<u8> {
+    let mut body = 1u32.to_le_bytes().to_vec();
+    body.extend(string(text));
+    chunk(0x2020, body)
+}
+
+/// A 1x1 RGBA file of the frames, each `(milliseconds, chunks)`
+fn file(frames: Vec<(u16, Vec<Vec<u8>>)>) -> Vec<u8> {
+    let mut bytes = vec![0; 128];
+    bytes[4..6].copy_from_slice(&0xA5E0u16.to_le_bytes());
+    bytes[6..8].copy_from_slice(&(frames.len() as u16).to_le_bytes());
+    bytes[8..10].copy_from_slice(&1u16.to_le_bytes());
+    bytes[10..12].copy_from_slice(&1u16.to_le_bytes());
+    bytes[12..14].copy_from_slice(&32u16.to_le_bytes());
+    bytes[14..18].copy_from_slice(&1u32.to_le_bytes());
+
+    for (duration, chunks) in frames {
+        let body: Vec<u8> = chunks.concat();
+        bytes.extend((body.len() as u32 + 16).to_le_bytes());
+        bytes.extend(0xF1FAu16.to_le_bytes());
+        bytes.extend((chunks.len() as u16).to_le_bytes());
+        bytes.extend(duration.to_le_bytes());
+        bytes.extend([0; 2]);
+        bytes.extend((chunks.len() as u32).to_le_bytes());
+        bytes.extend(body);
+    }
+    let len = bytes.len() as u32;
+    bytes[0..4].copy_from_slice(&len.to_le_bytes());
+    bytes
+}
+
+#[test]
+fn frames_and_tags_become_clips() {
+    let bytes = file(vec![
+        (
+            100,
+            vec![
+                layer("ice", true),
+                layer("sketch", false),
+                tags(&[("shimmer", 0, 2, 2, 0), ("crack", 0, 2, 1, 1)]),
+                cel(0, RED),
+                cel(1, WHITE),
+            ],
+        ),
+        (200, vec![cel(0, GREEN)]),
+        (300, vec![linked_cel(0, 0)]),
+    ]);
+    let aseprite = Aseprite::parse(&bytes).unwrap();
+
+    // the hidden layer is left out and the linked cel shows the first frame again
+    let pixels: Vec<&[u8]> = aseprite.frames.iter().map(|f| &f.pixels[..]).collect();
+    assert_eq!(pixels, [&RED[..], &GREEN[..], &RED[..]]);
+
+    let clips = aseprite.clips();
+    assert_eq!(clips[ALL_FRAMES].frames, [0, 1, 2]);
+
+    let shimmer = &clips["shimmer"];
+    assert_eq!(shimmer.frames, [0, 1, 2, 1]);
+    assert_eq!(shimmer.durations, [0.1, 0.2, 0.3, 0.2]);
+    assert_eq!(shimmer.mode, ClipMode::Loop);
+    assert_eq!(shimmer.frame(0.05, 0.), 0);
+    assert_eq!(shimmer.frame(0.35, 0.), 2);
+    assert_eq!(shimmer.frame(0.65, 0.), 1);
+    assert_eq!(shimmer.frame(0.85, 0.), 0);
+
+    let crack = &clips["crack"];
+    assert_eq!(crack.frames, [2, 1, 0]);
+    assert_eq!(crack.mode, ClipMode::Once);
+    assert_eq!(crack.frame(10., 0.), 0);
+}
+
+#[test]
+fn tags_past_the_last_frame_are_an_error() {
+    let bytes = file(vec![
+        (
+            100,
+            vec![
+                layer("ice", true),
+                tags(&[("melt", 0, 2, 0, 0)]),
+                cel(0, RED),
+            ],
+        ),
+        (100, vec![cel(0, GREEN)]),
+    ]);
+
+    let err = Aseprite::parse(&bytes).unwrap_err();
+    assert!(err.to_string().contains("melt"));
+}
+
+#[test]
+fn tags_with_life_as_user_data_follow_the_life() {
+    let bytes = file(vec![
+        (
+            100,
+            vec![
+                layer("ice", true),
+                tags(&[("melt", 0, 2, 0, 0), ("break", 1, 2, 0, 1)]),
+                user_data("life"),
+                user_data(""),
+                cel(0, RED),
+            ],
+        ),
+        (100, vec![cel(0, GREEN)]),
+        (100, vec![cel(0, WHITE)]),
+    ]);
+    let clips = Aseprite::parse(&bytes).unwrap().clips();
+
+    assert_eq!(clips["melt"].mode, ClipMode::Life);
+    assert_eq!(clips["melt"].frame(0., 0.5), 1);
+    assert_eq!(clips["break"].mode, ClipMode::Once);
+}
+
+#[test]
+fn files_without_pixels_are_an_error() {
+    let mut bytes = file(vec![(100, vec![layer("ice", true), cel(0, RED)])]);
+    bytes[8..10].copy_from_slice(&0u16.to_le_bytes());
+    assert!(Aseprite::parse(&bytes).is_err());
+
+    let mut empty_cel = 0u16.to_le_bytes().to_vec();
+    empty_cel.extend([0, 0, 0, 0, 255, 0, 0, 0, 0]);
+    empty_cel.extend([0; 5]);
+    empty_cel.extend([0, 0, 1, 0]);
+    let bytes = file(vec![(
+        100,
+        vec![layer("ice", true), chunk(0x2005, empty_cel)],
+    )]);
+    assert!(Aseprite::parse(&bytes).is_err());
+}
+
+#[test]
+fn palettes_past_256_colors_are_an_error() {
+    let mut palette = 100_000u32.to_le_bytes().to_vec();
+    palette.extend(0u32.to_le_bytes());
+    palette.extend(0u32.to_le_bytes());
+    palette.extend([0; 8]);
+    palette.extend([0, 0, 255, 0, 0, 255]);
+    let bytes = file(vec![(100, vec![chunk(0x2019, palette)])]);
+
+    let err = Aseprite::parse(&bytes).unwrap_err();
+    assert!(err.to_string().contains("256"));
+}
+
+#[test]
+fn aseprite_files_load_as_sheets() {
+    let mut app = TestApp::new();
+    let handle: Handle<AnimationSheet> = app.resource::<AssetServer>().load("Sprite-0001.aseprite");
+    app.update_until("the sheet", |world| {
+        // the file loads on another thread
+        thread::sleep(Duration::from_millis(1));
+        world.resource::<Assets<AnimationSheet>>().contains(&handle)
+    });
+
+    let sheets = app.resource::<Assets<AnimationSheet>>();
+    let sheet = sheets.get(&handle).unwrap();
+    assert_eq!(sheet.clips[ALL_FRAMES].frames, [0]);
+
+    let images = app.resource::<Assets<Image>>();
+    let image = images.get(sheet.image.as_ref().unwrap()).unwrap();
+    assert_eq!(image.size(), UVec2::new(1280, 720));
+    // the sky of the bottom layer
+    let sky = (10 * 1280 + 10) * 4;
+    assert_eq!(
+        image.data.as_ref().unwrap()[sky..sky + 4],
+        [0x63, 0x9b, 0xff, 0xff]
+    );
+}
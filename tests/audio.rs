@@ -0,0 +1,36 @@
+mod harness;
+
+use cozy_winter::{
+    GameState,
+    audio::{AudioManager, Sound},
+    events::NutDestroyed,
+};
+use harness::{TestApp, written};
+
+#[test]
+fn hit_loops_follow_the_laser() {
+    let mut app = TestApp::playing();
+    app.nut_in_laser();
+    app.update_frames(3);
+
+    let audio = app.resource::<AudioManager>();
+    assert!(audio.is_looping(Sound::NutHit));
+    assert!(!audio.is_looping(Sound::CubeHit));
+    assert!(audio.is_looping(Sound::LaserHum));
+
+    app.update_until("the nut to break", |world| {
+        !written::<NutDestroyed>(world).is_empty()
+    });
+    app.update_frames(2);
+    assert!(!app.resource::<AudioManager>().is_looping(Sound::NutHit));
+
+    // the cube in the laser instead
+    app.melt_cube();
+    app.update_frames(3);
+    assert!(app.resource::<AudioManager>().is_looping(Sound::CubeHit));
+
+    app.update_until_state(GameState::Shoping);
+    let audio = app.resource::<AudioManager>();
+    assert!(!audio.is_looping(Sound::CubeHit));
+    assert!(!audio.is_looping(Sound::LaserHum));
+}
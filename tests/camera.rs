@@ -0,0 +1,45 @@
+mod harness;
+
+use bevy::prelude::*;
+use cozy_winter::camera::{CameraSettings, CameraShake};
+use harness::TestApp;
+
+fn camera_transform(app: &mut TestApp) -> Transform {
+    let world = app.app.world_mut();
+    *world
+        .query_filtered::<&Transform, With<CameraShake>>()
+        .single(world)
+        .unwrap()
+}
+
+#[test]
+fn a_lost_cube_shakes_the_camera() {
+    let mut app = TestApp::playing();
+    app.melt_cube();
+
+    let mut shook = false;
+    for _ in 0..harness::MAX_FRAMES {
+        app.update();
+        if camera_transform(&mut app).translation.length() > 0.5 {
+            shook = true;
+            break;
+        }
+    }
+    assert!(shook);
+
+    // the shake dies down
+    app.update_frames(120);
+    assert_eq!(camera_transform(&mut app), Transform::default());
+}
+
+#[test]
+fn reduced_motion_keeps_the_camera_still() {
+    let mut app = TestApp::playing();
+    app.resource_mut::<CameraSettings>().reduced_motion = true;
+    app.melt_cube();
+
+    for _ in 0..300 {
+        app.update();
+        assert_eq!(camera_transform(&mut app), Transform::default());
+    }
+}
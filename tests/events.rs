@@ -0,0 +1,50 @@
+mod harness;
+
+use cozy_winter::{
+    Money, NutType,
+    events::{CubeDamaged, CubeLost, NutDamaged, NutDestroyed, RoundEnded, RoundStarted},
+    game_mode::GameMode,
+};
+use harness::{NUT_IN_LASER, TestApp, written};
+
+#[test]
+fn round_start_and_end_are_announced() {
+    let mut app = TestApp::playing();
+    let started = app.messages::<RoundStarted>();
+    assert_eq!(started.len(), 1);
+    assert_eq!(started[0].mode, GameMode::Classic);
+
+    app.melt_cube();
+    let mut damaged = false;
+    let mut lost = false;
+    app.update_until("the end of the round", |world| {
+        damaged |= !written::<CubeDamaged>(world).is_empty();
+        lost |= !written::<CubeLost>(world).is_empty();
+        !written::<RoundEnded>(world).is_empty()
+    });
+
+    assert!(damaged);
+    assert!(lost);
+    let ended = app.messages::<RoundEnded>();
+    assert_eq!(ended[0].mode, GameMode::Classic);
+    assert_eq!(ended[0].score, 0);
+}
+
+#[test]
+fn destroyed_nut_pays_its_value() {
+    let mut app = TestApp::playing();
+    app.nut_in_laser();
+
+    let mut damaged = false;
+    app.update_until("the nut to break", |world| {
+        damaged |= !written::<NutDamaged>(world).is_empty();
+        !written::<NutDestroyed>(world).is_empty()
+    });
+
+    assert!(damaged);
+    let destroyed = app.messages::<NutDestroyed>();
+    assert_eq!(destroyed[0].kind, NutType::Base);
+    assert_eq!(destroyed[0].value, 1);
+    assert!(destroyed[0].pos.distance(NUT_IN_LASER) < 0.5);
+    assert_eq!(app.resource::<Money>().0, 1);
+}
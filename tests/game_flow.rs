@@ -0,0 +1,148 @@
+mod harness;
+
+use bevy::prelude::*;
+use cozy_winter::{
+    GameState, Money, PlayerStats, UpgradeList, UpgradeType,
+    events::{RoundEnded, UpgradePurchased},
+    forest::{Cube, PlayerCube, SimTransform},
+    game_mode::{GameMode, HighScores, RunClock, RunScore},
+};
+use harness::{TestApp, written};
+
+fn upgrade<'a>(upgrades: &'a UpgradeList, title: &str, value_hint: &str) -> &'a UpgradeType {
+    upgrades
+        .0
+        .iter()
+        .find(|u| u.title == title && u.value_hint == value_hint)
+        .expect("the upgrade exists")
+}
+
+#[test]
+fn mode_button_starts_the_game() {
+    let mut app = TestApp::new();
+    assert_eq!(app.state(), GameState::Start);
+
+    app.click_button("Classic");
+    app.update_until_state(GameState::Playing);
+
+    assert_eq!(app.count::<PlayerCube>(), 1);
+    assert_eq!(app.resource::<Money>().0, 0);
+}
+
+#[test]
+fn cube_follows_the_cursor() {
+    let mut app = TestApp::playing();
+
+    app.click_at(Vec2::new(120., 80.));
+    app.update_frames(2);
+
+    let world = app.app.world_mut();
+    let sim = world
+        .query_filtered::<&SimTransform, With<PlayerCube>>()
+        .single(world)
+        .unwrap();
+    assert!(
+        sim.current
+            .translation
+            .truncate()
+            .distance(Vec2::new(120., 80.))
+            < 0.5
+    );
+}
+
+#[test]
+fn melted_cube_opens_the_shop() {
+    let mut app = TestApp::playing();
+
+    app.melt_cube();
+    app.update_until_state(GameState::Shoping);
+
+    assert_eq!(app.count::<PlayerCube>(), 0);
+}
+
+#[test]
+fn buying_upgrades_changes_money_and_stats() {
+    let mut app = TestApp::shopping();
+    app.resource_mut::<Money>().0 = 10;
+    let dmg = app.resource::<PlayerStats>().dmg;
+
+    app.click_button("Damage\n+ 5");
+    app.update();
+    assert_eq!(app.resource::<Money>().0, 9);
+    assert_eq!(app.resource::<PlayerStats>().dmg, dmg + 5.);
+    let purchased = app.messages::<UpgradePurchased>();
+    assert_eq!(purchased.len(), 1);
+    assert_eq!((purchased[0].cost, purchased[0].level), (1, 1));
+
+    app.click_button("Damage\n+ 20");
+    app.update();
+    assert_eq!(app.resource::<Money>().0, 4);
+    assert_eq!(app.resource::<PlayerStats>().dmg, dmg + 25.);
+
+    let upgrades = app.resource::<UpgradeList>();
+    assert_eq!(upgrade(upgrades, "Damage", "+ 5").cur_up_count, 1);
+    assert_eq!(upgrade(upgrades, "Damage", "+ 20").cur_up_count, 1);
+    // the bigger damage upgrade gets more expensive
+    assert_eq!(upgrade(upgrades, "Damage", "+ 20").cost, 7);
+}
+
+#[test]
+fn upgrade_without_money_changes_nothing() {
+    let mut app = TestApp::shopping();
+    app.resource_mut::<Money>().0 = 0;
+    let dmg = app.resource::<PlayerStats>().dmg;
+
+    app.click_button("Damage\n+ 20");
+    app.update();
+
+    assert!(app.messages::<UpgradePurchased>().is_empty());
+    assert_eq!(app.resource::<Money>().0, 0);
+    assert_eq!(app.resource::<PlayerStats>().dmg, dmg);
+    let upgrades = app.resource::<UpgradeList>();
+    assert_eq!(upgrade(upgrades, "Damage", "+ 20").cur_up_count, 0);
+}
+
+#[test]
+fn new_round_keeps_the_upgrades() {
+    let mut app = TestApp::shopping();
+    app.resource_mut::<Money>().0 = 1;
+
+    app.click_button("Max Life\n+ 20");
+    app.update();
+    app.click_button("New Round");
+    app.update_until_state(GameState::Playing);
+    app.update();
+
+    let world = app.app.world_mut();
+    let cube = world
+        .query_filtered::<&Cube, With<PlayerCube>>()
+        .single(world)
+        .unwrap();
+    assert_eq!(cube.max_life, 220.);
+    assert_eq!(cube.life, 220.);
+}
+
+#[test]
+fn run_score_and_clock_go_on_after_the_shop() {
+    let mut app = TestApp::shopping();
+    let clock = app.resource::<RunClock>().0;
+    assert!(clock > 0.);
+    // as if the first rounds brought in that much
+    app.resource_mut::<RunScore>().0 = 500;
+
+    app.click_button("New Round");
+    app.update_until_state(GameState::Playing);
+    app.update_frames(10);
+    assert!(app.resource::<RunClock>().0 > clock);
+
+    app.melt_cube();
+    app.update_until("the end of the round", |world| {
+        !written::<RoundEnded>(world).is_empty()
+    });
+    let ended = app.messages::<RoundEnded>();
+    assert_eq!(ended[0].run_score, 500 + ended[0].score);
+    assert_eq!(
+        app.resource::<HighScores>().get(GameMode::Classic),
+        ended[0].run_score
+    );
+}
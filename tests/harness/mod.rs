@@ -0,0 +1,298 @@
+//! Runs the game without a window or renderer.
+//!
+//! Build a [`TestApp`], feed it synthetic cursor, mouse and button input and step it frame by frame.
+//! Every frame is exactly one tick of the forest, so a test behaves the same on every machine.
+
+// not every test file uses every helper
+#![allow(dead_code)]
+
+use bevy::{
+    audio::AudioPlugin,
+    camera::RenderTargetInfo,
+    image::TextureAtlasPlugin,
+    input::{ButtonState, InputPlugin, mouse::MouseButtonInput},
+    prelude::*,
+    state::app::StatesPlugin,
+    time::TimeUpdateStrategy,
+    window::{ExitCondition, PrimaryWindow, WindowResolution},
+};
+use cozy_winter::{
+    CozyWinterPlugin, GameState, NutType, camera::CameraShake, forest::SpawnNutMessage,
+    game_mode::HighScoreStorage, options::LaunchOptions,
+};
+
+/// Size of the fake window in logical pixels
+pub const WINDOW_SIZE: UVec2 = UVec2::new(1280, 720);
+
+/// Upper bound of frames a test waits for something to happen
+pub const MAX_FRAMES: usize = 2000;
+
+/// Where [`TestApp::nut_in_laser`] puts the nut
+pub const NUT_IN_LASER: Vec2 = Vec2::new(0., -200.);
+
+/// Messages of type `M` written in the last two frames, for checks inside [`TestApp::update_until`]
+pub fn written<M: Message + Clone>(world: &World) -> Vec<M> {
+    let messages = world.resource::<Messages<M>>();
+    messages.get_cursor().read(messages).cloned().collect()
+}
+
+pub struct TestApp {
+    pub app: App,
+}
+
+impl TestApp {
+    /// The whole game on the title screen
+    pub fn new() -> Self {
+        Self::with_options(LaunchOptions::default())
+    }
+
+    /// The whole game started like with these command line options
+    pub fn with_options(options: LaunchOptions) -> Self {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            StatesPlugin,
+            TransformPlugin,
+            InputPlugin,
+            WindowPlugin {
+                primary_window: Some(Window {
+                    resolution: WindowResolution::new(WINDOW_SIZE.x, WINDOW_SIZE.y),
+                    ..Default::default()
+                }),
+                exit_condition: ExitCondition::DontExit,
+                close_when_requested: false,
+                ..Default::default()
+            },
+            AssetPlugin::default(),
+            ImagePlugin::default(),
+            TextureAtlasPlugin,
+            AudioPlugin::default(),
+        ))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(
+            Time::<Fixed>::default().timestep(),
+        ))
+        .insert_resource(options)
+        // the high scores of the tests do not end up in the checkout
+        .insert_resource(HighScoreStorage::Memory)
+        .add_plugins(CozyWinterPlugin);
+
+        // startup spawns the camera
+        app.update();
+
+        let mut test = Self { app };
+        test.setup_camera(WINDOW_SIZE);
+        test.update();
+        test
+    }
+
+    /// A fresh Classic round
+    pub fn playing() -> Self {
+        let mut test = Self::new();
+        test.click_button("Classic");
+        test.update_until_state(GameState::Playing);
+        test
+    }
+
+    /// The shop after the first Classic round
+    pub fn shopping() -> Self {
+        let mut test = Self::playing();
+        test.melt_cube();
+        test.update_until_state(GameState::Shoping);
+        // the upgrade buttons are spawned the frame after the shop opens
+        test.update();
+        test
+    }
+
+    /// Without a renderer nobody computes the camera viewport, so it is set to the fake window
+    fn setup_camera(&mut self, window_size: UVec2) {
+        let world = self.app.world_mut();
+        let mut cameras = world.query::<(&mut Camera, &Projection)>();
+        for (mut camera, projection) in cameras.iter_mut(world) {
+            let mut projection = projection.clone();
+            projection.update(window_size.x as f32, window_size.y as f32);
+            camera.computed.clip_from_view = projection.get_clip_from_view();
+            camera.computed.target_info = Some(RenderTargetInfo {
+                physical_size: window_size,
+                scale_factor: 1.,
+            });
+        }
+    }
+
+    /// Gives the fake window a new size in pixels, the game fits to it in the next frame
+    pub fn resize_window(&mut self, size: UVec2) {
+        let window = self.window();
+        let world = self.app.world_mut();
+        world
+            .entity_mut(window)
+            .get_mut::<Window>()
+            .unwrap()
+            .resolution
+            .set_physical_resolution(size.x, size.y);
+        self.setup_camera(size);
+        self.update();
+    }
+
+    pub fn update(&mut self) {
+        self.app.update();
+    }
+
+    pub fn update_frames(&mut self, frames: usize) {
+        for _ in 0..frames {
+            self.app.update();
+        }
+    }
+
+    /// Steps until `done` is true, panics after [`MAX_FRAMES`]
+    pub fn update_until(&mut self, what: &str, mut done: impl FnMut(&mut World) -> bool) {
+        for _ in 0..MAX_FRAMES {
+            if done(self.app.world_mut()) {
+                return;
+            }
+            self.app.update();
+        }
+        panic!("Gave up waiting for {} after {} frames", what, MAX_FRAMES);
+    }
+
+    pub fn update_until_state(&mut self, state: GameState) {
+        let what = format!("{:?}", state);
+        self.update_until(&what, |world| {
+            *world.resource::<State<GameState>>().get() == state
+        });
+    }
+
+    pub fn state(&self) -> GameState {
+        self.app
+            .world()
+            .resource::<State<GameState>>()
+            .get()
+            .clone()
+    }
+
+    pub fn resource<R: Resource>(&self) -> &R {
+        self.app.world().resource::<R>()
+    }
+
+    pub fn resource_mut<R: Resource>(&mut self) -> Mut<'_, R> {
+        self.app.world_mut().resource_mut::<R>()
+    }
+
+    /// Number of entities with the component `C`
+    pub fn count<C: Component>(&mut self) -> usize {
+        let world = self.app.world_mut();
+        world.query_filtered::<(), With<C>>().iter(world).count()
+    }
+
+    /// Messages of type `M` written in the last two frames
+    pub fn messages<M: Message + Clone>(&self) -> Vec<M> {
+        written(self.app.world())
+    }
+
+    pub fn window(&mut self) -> Entity {
+        let world = self.app.world_mut();
+        world
+            .query_filtered::<Entity, With<PrimaryWindow>>()
+            .single(world)
+            .expect("the test app has a primary window")
+    }
+
+    /// Moves the cursor over the world position, the game sees it in the next frame
+    pub fn move_cursor(&mut self, world_pos: Vec2) {
+        let window = self.window();
+        let world = self.app.world_mut();
+        let (camera, shake) = world
+            .query::<(&Camera, &CameraShake)>()
+            .single(world)
+            .expect("the game has one camera");
+        // the game reads the cursor like the camera does not shake
+        let position = camera
+            .world_to_viewport(&shake.rest.into(), world_pos.extend(0.))
+            .expect("the position is in front of the camera");
+
+        world
+            .entity_mut(window)
+            .get_mut::<Window>()
+            .unwrap()
+            .set_cursor_position(Some(position));
+        world.write_message(CursorMoved {
+            window,
+            position,
+            delta: None,
+        });
+    }
+
+    pub fn press_mouse(&mut self, button: MouseButton) {
+        self.mouse_input(button, ButtonState::Pressed);
+    }
+
+    pub fn release_mouse(&mut self, button: MouseButton) {
+        self.mouse_input(button, ButtonState::Released);
+    }
+
+    fn mouse_input(&mut self, button: MouseButton, state: ButtonState) {
+        let window = self.window();
+        self.app.world_mut().write_message(MouseButtonInput {
+            button,
+            state,
+            window,
+        });
+    }
+
+    /// Left click at the world position, takes one frame
+    pub fn click_at(&mut self, world_pos: Vec2) {
+        self.move_cursor(world_pos);
+        self.press_mouse(MouseButton::Left);
+        self.update();
+        self.release_mouse(MouseButton::Left);
+    }
+
+    /// The UI button with a text starting with `label`
+    pub fn find_button(&mut self, label: &str) -> Entity {
+        let world = self.app.world_mut();
+        let mut buttons = world.query_filtered::<(Entity, &Children), With<Button>>();
+        let mut texts = world.query::<&Text>();
+
+        buttons
+            .iter(world)
+            .find(|(_, children)| {
+                children.iter().any(|child| {
+                    texts
+                        .get(world, child)
+                        .is_ok_and(|t| t.0.starts_with(label))
+                })
+            })
+            .map(|(entity, _)| entity)
+            .unwrap_or_else(|| panic!("No button '{}'", label))
+    }
+
+    /// Presses and releases the UI button like the pointer would, takes one frame
+    pub fn press_button(&mut self, button: Entity) {
+        self.set_interaction(button, Interaction::Pressed);
+        self.update();
+        self.set_interaction(button, Interaction::None);
+    }
+
+    pub fn click_button(&mut self, label: &str) {
+        let button = self.find_button(label);
+        self.press_button(button);
+    }
+
+    fn set_interaction(&mut self, button: Entity, interaction: Interaction) {
+        // the button may be gone after it was pressed
+        if let Ok(mut entity) = self.app.world_mut().get_entity_mut(button) {
+            entity.insert(interaction);
+        }
+    }
+
+    /// Parks the cube in the laser until it is melted
+    pub fn melt_cube(&mut self) {
+        self.move_cursor(Vec2::new(0., -200.));
+    }
+
+    /// The cube out of the way and a plain nut right in the laser, the game sees both in the next frame
+    pub fn nut_in_laser(&mut self) {
+        self.move_cursor(Vec2::new(200., 150.));
+        self.app
+            .world_mut()
+            .write_message(SpawnNutMessage(Some(NUT_IN_LASER), NutType::Base));
+    }
+}
@@ -0,0 +1,54 @@
+mod harness;
+
+use bevy::prelude::*;
+use cozy_winter::{camera::CameraShake, letterbox::ARENA_SIZE};
+use harness::TestApp;
+
+fn game_camera(app: &mut TestApp) -> Camera {
+    let world = app.app.world_mut();
+    world
+        .query_filtered::<&Camera, With<CameraShake>>()
+        .single(world)
+        .unwrap()
+        .clone()
+}
+
+#[test]
+fn wide_window_gets_bars_on_the_sides() {
+    let mut app = TestApp::playing();
+    app.resize_window(UVec2::new(1920, 900));
+
+    // 900 / 720 = 1.25, so the arena is 1600 wide and 160 left over on each side
+    let viewport = game_camera(&mut app).viewport.unwrap();
+    assert_eq!(viewport.physical_position, UVec2::new(160, 0));
+    assert_eq!(viewport.physical_size, UVec2::new(1600, 900));
+    assert!((app.resource::<UiScale>().0 - 1.25).abs() < 0.001);
+}
+
+#[test]
+fn cursor_maps_to_the_same_arena_position_at_any_size() {
+    let mut app = TestApp::playing();
+    for size in [
+        UVec2::new(1280, 720),
+        UVec2::new(1920, 900),
+        UVec2::new(800, 1000),
+    ] {
+        app.resize_window(size);
+        let camera = game_camera(&mut app);
+        let viewport = camera.logical_viewport_rect().unwrap();
+
+        // the top right corner of the arena
+        let world_pos = camera
+            .viewport_to_world_2d(
+                &GlobalTransform::IDENTITY,
+                viewport.max.with_y(viewport.min.y),
+            )
+            .unwrap();
+        assert!(
+            world_pos.distance(ARENA_SIZE / 2.) < 1.,
+            "{:?} at {}",
+            world_pos,
+            size
+        );
+    }
+}
@@ -0,0 +1,46 @@
+mod harness;
+
+use cozy_winter::{
+    GameState,
+    music::{MusicChannel, Track},
+};
+use harness::TestApp;
+
+fn levels(app: &mut TestApp) -> Vec<(Track, f32)> {
+    let world = app.app.world_mut();
+    let mut channels = world.query::<&MusicChannel>();
+    channels
+        .iter(world)
+        .map(|channel| (channel.track, channel.level))
+        .collect()
+}
+
+fn level(app: &mut TestApp, track: Track) -> f32 {
+    levels(app)
+        .into_iter()
+        .find(|(t, _)| *t == track)
+        .map(|(_, level)| level)
+        .unwrap()
+}
+
+#[test]
+fn music_crossfades_to_the_track_of_the_state() {
+    let mut app = TestApp::new();
+    app.update_frames(200);
+    assert_eq!(level(&mut app, Track::Title), 1.);
+    assert_eq!(level(&mut app, Track::Forest), 0.);
+
+    app.click_button("Classic");
+    app.update_until_state(GameState::Playing);
+    app.update_frames(10);
+    // both are audible in the middle of the fade
+    let title = level(&mut app, Track::Title);
+    let forest = level(&mut app, Track::Forest);
+    assert!(0. < title && title < 1.);
+    assert!(0. < forest && forest < 1.);
+
+    app.update_frames(200);
+    assert_eq!(level(&mut app, Track::Title), 0.);
+    assert_eq!(level(&mut app, Track::Forest), 1.);
+    assert_eq!(level(&mut app, Track::Shop), 0.);
+}
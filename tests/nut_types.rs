@@ -0,0 +1,27 @@
+use cozy_winter::{NutType, PlayerStats};
+use rand::{SeedableRng, rngs::StdRng};
+
+#[test]
+fn rolled_nuts_follow_their_chances() {
+    let mut rng = StdRng::seed_from_u64(42);
+    let rolls: Vec<NutType> = (0..100_000).map(|_| NutType::roll(&mut rng)).collect();
+
+    for (kind, chance) in NutType::CHANCES {
+        let count = rolls.iter().filter(|rolled| **rolled == kind).count();
+        let expected = chance as usize * 100;
+        assert!(
+            count.abs_diff(expected) <= expected / 5 + 10,
+            "{:?}: {} of {}",
+            kind,
+            count,
+            expected
+        );
+    }
+}
+
+#[test]
+fn average_value_counts_the_rare_nuts() {
+    let stats = PlayerStats::default();
+    // 950 * 1 + 35 * 10 + 10 * 20 + 4 * 50 + 1 * 100 of a thousand nuts
+    assert_eq!(stats.average_value(), 1.8);
+}
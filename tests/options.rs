@@ -0,0 +1,59 @@
+mod harness;
+
+use bevy::prelude::*;
+use cozy_winter::{
+    GameState, Money, PlayerStats,
+    forest::{Cube, LaserPoints, PlayerCube},
+    game_mode::GameMode,
+    options::LaunchOptions,
+    rng::SeedSetting,
+};
+use harness::TestApp;
+
+#[test]
+fn state_option_skips_the_title() {
+    let mut app = TestApp::with_options(LaunchOptions {
+        state: Some(GameState::Shoping),
+        mode: Some(GameMode::Timed),
+        money: Some(500),
+        ..Default::default()
+    });
+    app.update_until_state(GameState::Shoping);
+
+    assert_eq!(*app.resource::<GameMode>(), GameMode::Timed);
+    assert_eq!(app.resource::<Money>().0, 500);
+}
+
+#[test]
+fn money_and_preset_wait_for_the_first_round() {
+    let mut app = TestApp::with_options(LaunchOptions {
+        money: Some(42),
+        preset: Some("strong".into()),
+        ..Default::default()
+    });
+    assert_eq!(app.state(), GameState::Start);
+    // no nut of this seed is in the way of the laser
+    app.resource_mut::<SeedSetting>().0 = Some(7);
+
+    app.click_button("Classic");
+    app.update_until_state(GameState::Playing);
+
+    let strong = PlayerStats::preset("strong").unwrap();
+    assert_eq!(app.resource::<Money>().0, 42);
+    assert_eq!(app.resource::<PlayerStats>().dmg, strong.dmg);
+
+    // the forest of the first round is already built with the preset
+    let world = app.app.world_mut();
+    let cube = world
+        .query_filtered::<&Cube, With<PlayerCube>>()
+        .single(world)
+        .unwrap();
+    assert_eq!(cube.max_life, strong.cube_max_life);
+    let laser: f32 = world
+        .resource::<LaserPoints>()
+        .list
+        .iter()
+        .map(|(start, end)| start.distance(*end))
+        .sum();
+    assert!((laser - strong.laser_length).abs() < 0.5);
+}
@@ -0,0 +1,28 @@
+mod harness;
+
+use cozy_winter::{
+    events::NutDestroyed,
+    popups::{Popup, PopupKind, PopupSettings},
+};
+use harness::{TestApp, written};
+
+#[test]
+fn broken_nut_rises_as_a_money_popup() {
+    let mut app = TestApp::playing();
+    app.resource_mut::<PopupSettings>().damage_numbers = true;
+    app.nut_in_laser();
+    app.update_until("the nut to break", |world| {
+        !written::<NutDestroyed>(world).is_empty()
+    });
+    app.update();
+
+    let mut popups = app.app.world_mut().query::<&Popup>();
+    let popups: Vec<&Popup> = popups.iter(app.app.world()).collect();
+    let money: Vec<&&Popup> = popups
+        .iter()
+        .filter(|popup| popup.kind == PopupKind::Money)
+        .collect();
+    assert_eq!(money.len(), 1);
+    assert_eq!(money[0].value, 1);
+    assert!(popups.iter().any(|popup| popup.kind == PopupKind::Damage));
+}
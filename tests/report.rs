@@ -0,0 +1,18 @@
+use cozy_winter::report::{Row, table};
+
+fn row(cells: &[&str]) -> Row {
+    cells.iter().map(|cell| cell.to_string()).collect()
+}
+
+#[test]
+fn columns_fit_the_widest_cell() {
+    let lines = table(
+        &row(&["round", "money"]),
+        &[row(&["1", "5"]), row(&["12", "12345678"])],
+    );
+
+    assert_eq!(
+        lines,
+        ["round |    money", "    1 |        5", "   12 | 12345678"]
+    );
+}
@@ -0,0 +1,26 @@
+mod harness;
+
+use cozy_winter::{GameState, particles::Particle, snow::SnowSettings};
+use harness::TestApp;
+
+#[test]
+fn snow_falls_in_the_forest_only() {
+    let mut app = TestApp::playing();
+    app.update_frames(60);
+    assert!(app.count::<Particle>() > 0);
+
+    app.melt_cube();
+    app.update_until_state(GameState::Shoping);
+    app.update();
+    assert_eq!(app.count::<Particle>(), 0);
+}
+
+#[test]
+fn no_snow_at_zero_density() {
+    let mut app = TestApp::new();
+    app.resource_mut::<SnowSettings>().density = 0.;
+    app.click_button("Classic");
+    app.update_until_state(GameState::Playing);
+    app.update_frames(60);
+    assert_eq!(app.count::<Particle>(), 0);
+}
@@ -0,0 +1,80 @@
+mod harness;
+
+use std::{collections::HashMap, thread, time::Duration};
+
+use bevy::prelude::*;
+use cozy_winter::sprite_animation::{AnimationSheet, ClipMode, SpriteAnimation, SpriteClip};
+use harness::TestApp;
+
+fn clip(frames: &[usize], fps: f32, mode: ClipMode) -> SpriteClip {
+    SpriteClip {
+        frames: frames.to_vec(),
+        fps,
+        durations: Vec::new(),
+        mode,
+    }
+}
+
+fn atlas_index(app: &TestApp, entity: Entity) -> Option<usize> {
+    let sprite = app.app.world().get::<Sprite>(entity).unwrap();
+    sprite.texture_atlas.as_ref().map(|atlas| atlas.index)
+}
+
+#[test]
+fn sheets_of_the_ice_load_with_their_clips() {
+    let mut app = TestApp::new();
+    let handle: Handle<AnimationSheet> = app.resource::<AssetServer>().load("ice_nut.aseprite");
+    app.update_until("the sheet", |world| {
+        // the file loads on another thread
+        thread::sleep(Duration::from_millis(1));
+        world.resource::<Assets<AnimationSheet>>().contains(&handle)
+    });
+
+    let sheets = app.resource::<Assets<AnimationSheet>>();
+    let sheet = sheets.get(&handle).unwrap();
+    assert_eq!(sheet.clips["melt"].mode, ClipMode::Life);
+    assert_eq!(sheet.clips["melt"].frames.len(), 5);
+    assert_eq!(sheet.clips["break"].mode, ClipMode::Once);
+}
+
+#[test]
+fn clips_follow_the_life_and_the_time() {
+    let mut app = TestApp::new();
+    let sheet = AnimationSheet {
+        image: None,
+        layout: Handle::default(),
+        clips: HashMap::from([
+            (
+                "melt".to_string(),
+                clip(&[0, 1, 2, 3, 4], 0., ClipMode::Life),
+            ),
+            ("break".to_string(), clip(&[3, 4], 12., ClipMode::Once)),
+        ]),
+    };
+    let handle = app.resource_mut::<Assets<AnimationSheet>>().add(sheet);
+
+    let mut animation = SpriteAnimation::new(handle, "melt");
+    animation.progress = 0.5;
+    let entity = app
+        .app
+        .world_mut()
+        .spawn((Sprite::default(), animation))
+        .id();
+    app.update();
+    assert_eq!(atlas_index(&app, entity), Some(2));
+
+    app.app
+        .world_mut()
+        .get_mut::<SpriteAnimation>(entity)
+        .unwrap()
+        .play("break");
+    app.update();
+    assert_eq!(atlas_index(&app, entity), Some(3));
+    // a once clip stays on its last frame
+    app.update_frames(64);
+    assert_eq!(atlas_index(&app, entity), Some(4));
+
+    let shimmer = clip(&[0, 1, 2], 10., ClipMode::Loop);
+    assert_eq!(shimmer.frame(0.15, 0.), 1);
+    assert_eq!(shimmer.frame(0.35, 0.), 0);
+}